@@ -1,6 +1,9 @@
 use qqbot_sdk::{
     bot::{Bot, BotConfig, message::MessageBuilder},
-    event::{handler::EventHandler, model::Event},
+    event::{
+        handler::EventHandler,
+        model::{Event, EventEnvelope, Intents},
+    },
     http::api::reaction::EmojiReactionDescriptor,
     model::{Emoji, RawEmoji},
 };
@@ -13,11 +16,14 @@ async fn main() {
 pub struct EchoHandler;
 
 impl EventHandler for EchoHandler {
-    fn would_handle(&self, _event: &Event, _bot: &Bot) -> bool {
+    fn intents(&self) -> Intents {
+        Intents::GUILD_MESSAGES | Intents::PUBLIC_GUILD_MESSAGES
+    }
+    fn would_handle(&self, _event: &EventEnvelope, _bot: &Bot) -> bool {
         true
     }
-    async fn handle(&self, event: Event, ctx: &Bot) -> Result<(), qqbot_sdk::Error> {
-        match event {
+    async fn handle(&self, event: EventEnvelope, ctx: &Bot) -> Result<(), qqbot_sdk::Error> {
+        match event.event {
             Event::MessageCreate(m) if !m.author.bot => {
                 tracing::info!("message: {:?}", m);
                 // 贴猴
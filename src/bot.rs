@@ -13,9 +13,17 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
 use crate::{
-    event::{EventService, implement::webhook::WebHookServiceAppConfig},
+    event::{
+        EventService,
+        implement::{
+            webhook::{WebHookRouterConfig, WebHookService, WebHookServiceAppConfig},
+            websocket::{MemorySessionStore, ShardManager, WebSocketServiceConfig},
+        },
+        model::{Event, Intents},
+        queue::QueueStats,
+    },
     http::client::reqwest_client::ApiClient,
-    model::Guild,
+    model::{Channel, ChannelId, Guild, GuildId, User},
 };
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct BotConfig {
@@ -30,6 +38,7 @@ pub struct BotInner {
     pub(crate) ct: tokio_util::sync::CancellationToken,
     pub(crate) config: BotConfig,
     pub(crate) cache: BotCache,
+    pub(crate) msg_seq: message::MessageSeqCounter,
 }
 
 impl BotInner {}
@@ -67,26 +76,73 @@ impl Bot {
             ct: tokio_util::sync::CancellationToken::new(),
             config,
             cache: BotCache::default(),
+            msg_seq: message::MessageSeqCounter::default(),
         });
 
         Self { inner, context: () }
     }
-    pub async fn start_webhook_service(&self, bind: SocketAddr) -> crate::Result<()> {
+    /// 启动 webhook 服务，返回事件队列的计数器
+    pub async fn start_webhook_service(&self, bind: SocketAddr) -> crate::Result<Arc<QueueStats>> {
+        self.start_webhook_service_with_config(WebHookServiceAppConfig::new(bind))
+            .await
+    }
+    pub async fn start_webhook_service_with_config(
+        &self,
+        config: WebHookServiceAppConfig,
+    ) -> crate::Result<Arc<QueueStats>> {
+        let service =
+            WebHookService::run(config, &self.config.secret, self.ct.child_token()).await?;
+        let stats = service.stats();
+        self.event_service.spawn(service)?;
+        Ok(stats)
+    }
+    /// 获取 webhook 的 axum 路由，用于挂载到已有的 axum 应用中，收到的事件同样交给 [`EventService`]
+    ///
+    /// 同时返回 webhook 事件队列的计数器
+    ///
+    /// ```rust,no_run,ignore
+    /// let (router, stats) = bot.webhook_router()?;
+    /// let app = axum::Router::new().nest_service("/qqbot/callback", router);
+    /// ```
+    pub fn webhook_router(&self) -> crate::Result<(axum::Router, Arc<QueueStats>)> {
+        self.webhook_router_with_config(WebHookRouterConfig::default())
+    }
+    pub fn webhook_router_with_config(
+        &self,
+        config: WebHookRouterConfig,
+    ) -> crate::Result<(axum::Router, Arc<QueueStats>)> {
+        let (service, router) = WebHookService::mount(config, &self.config.secret)?;
+        let stats = service.stats();
+        self.event_service.spawn(service)?;
+        Ok((router, stats))
+    }
+    /// 启动 websocket 连接，分片数使用 `/gateway/bot` 的建议值
+    ///
+    /// 实际订阅的事件是 `intents` 加上已注册处理器的 [`EventService::required_intents`]，所以应当先注册处理器
+    pub async fn start_websocket_service(&self, intents: Intents) -> crate::Result<ShardManager> {
         const DEFAULT_CHANNEL_SIZE: usize = 4096;
-        // 16 MB
-        const DEFAULT_BODY_SIZE: usize = 16 * 1024 * 1024;
-        let service = crate::event::implement::webhook::WebHookService::run(
-            WebHookServiceAppConfig {
-                bind,
-                channel_size: DEFAULT_CHANNEL_SIZE,
-                max_body_size: DEFAULT_BODY_SIZE,
-            },
-            &self.config.secret,
+        self.start_websocket_service_with_config(WebSocketServiceConfig {
+            intents,
+            shards: None,
+            channel_size: DEFAULT_CHANNEL_SIZE,
+            session_store: Arc::new(MemorySessionStore::new()),
+        })
+        .await
+    }
+    pub async fn start_websocket_service_with_config(
+        &self,
+        mut config: WebSocketServiceConfig,
+    ) -> crate::Result<ShardManager> {
+        config.intents |= self.event_service.required_intents().await;
+        let service = crate::event::implement::websocket::WebSocketService::run(
+            config,
+            self.api_client.clone(),
             self.ct.child_token(),
         )
         .await?;
+        let shard_manager = service.shard_manager().clone();
         self.event_service.spawn(service)?;
-        Ok(())
+        Ok(shard_manager)
     }
     pub fn event_service(&self) -> &EventService {
         &self.inner.event_service
@@ -124,10 +180,20 @@ impl<C: Clone> BotRef<C> {
 
 #[derive(Debug, Clone, Default)]
 pub struct BotCache {
-    guilds: Arc<RwLock<HashMap<u64, Guild>>>,
+    guilds: Arc<RwLock<HashMap<GuildId, Guild>>>,
+    channels: Arc<RwLock<HashMap<ChannelId, Channel>>>,
+    /// 机器人自己，用 std 的锁以便在 `would_handle` 中同步读取
+    me: Arc<std::sync::RwLock<Option<User>>>,
     // users: Arc<RwLock<HashMap<u64, User>>>,
 }
 impl BotCache {
+    pub fn cache_me(&self, user: User) {
+        *self.me.write().unwrap_or_else(|e| e.into_inner()) = Some(user);
+    }
+    /// 机器人自己的信息，调用 [`Bot::about_me`] 之后才有值
+    pub fn get_me(&self) -> Option<User> {
+        self.me.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
     pub async fn cache_guild(&self, guild: Guild) {
         self.guilds.write().await.insert(guild.id, guild);
     }
@@ -137,10 +203,61 @@ impl BotCache {
             guilds_cache.insert(guild.id, guild);
         }
     }
-    pub async fn get_guild(&self, id: u64) -> Option<Guild> {
+    /// 移除频道，同时移除这个频道下的所有子频道
+    pub async fn remove_guild(&self, id: GuildId) -> Option<Guild> {
+        self.channels
+            .write()
+            .await
+            .retain(|_, channel| channel.guild_id != id);
+        self.guilds.write().await.remove(&id)
+    }
+    pub async fn get_guild(&self, id: GuildId) -> Option<Guild> {
         self.guilds.read().await.get(&id).cloned()
     }
     pub async fn get_guilds_count(&self) -> usize {
         self.guilds.read().await.len()
     }
+    pub async fn cache_channel(&self, channel: Channel) {
+        self.channels.write().await.insert(channel.id, channel);
+    }
+    pub async fn remove_channel(&self, id: ChannelId) -> Option<Channel> {
+        self.channels.write().await.remove(&id)
+    }
+    pub async fn get_channel(&self, id: ChannelId) -> Option<Channel> {
+        self.channels.read().await.get(&id).cloned()
+    }
+    /// 频道下已缓存的子频道
+    pub async fn get_guild_channels(&self, guild_id: GuildId) -> Vec<Channel> {
+        self.channels
+            .read()
+            .await
+            .values()
+            .filter(|channel| channel.guild_id == guild_id)
+            .cloned()
+            .collect()
+    }
+    /// 根据频道和子频道事件更新缓存
+    pub(crate) async fn update_by_event(&self, event: &Event) {
+        match event {
+            Event::GuildCreate(guild) => self.cache_guild(guild.guild.clone()).await,
+            Event::GuildUpdate(guild) => {
+                let mut guild = guild.guild.clone();
+                // 推送的频道对象中没有 owner 字段，沿用之前的值
+                if let Some(cached) = self.get_guild(guild.id).await {
+                    guild.owner = cached.owner;
+                }
+                self.cache_guild(guild).await
+            }
+            Event::GuildDelete(guild) => {
+                self.remove_guild(guild.guild.id).await;
+            }
+            Event::ChannelCreate(channel) | Event::ChannelUpdate(channel) => {
+                self.cache_channel(channel.channel.clone()).await
+            }
+            Event::ChannelDelete(channel) => {
+                self.remove_channel(channel.channel.id).await;
+            }
+            _ => {}
+        }
+    }
 }
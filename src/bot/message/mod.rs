@@ -1,4 +1,8 @@
-use crate::model::{MessageBotRecieved, MessageId, MessageReference, MessageSend};
+use std::{collections::HashMap, time::Duration};
+
+use tokio::{sync::Mutex, time::Instant};
+
+use crate::model::{MessageBotRecieved, MessageId, MessageMedia, MessageReference, MessageSend};
 
 #[derive(Debug, Default)]
 pub struct MessageBuilder<'a> {
@@ -6,6 +10,8 @@ pub struct MessageBuilder<'a> {
     message_reference: Option<MessageReference>,
     images: Vec<&'a str>,
     reply_to: Option<MessageId>,
+    reply_to_event: Option<&'a str>,
+    media: Option<MessageMedia>,
 }
 
 impl<'a> MessageBuilder<'a> {
@@ -30,6 +36,16 @@ impl<'a> MessageBuilder<'a> {
         self.reply_to = Some(message_id);
         self
     }
+    /// 回复一个事件，用于发送被动消息
+    pub fn reply_to_event(mut self, event_id: &'a str) -> Self {
+        self.reply_to_event = Some(event_id);
+        self
+    }
+    /// 富媒体消息，可以直接传入上传接口返回的 [`Media`](crate::model::Media)
+    pub fn media(mut self, media: impl Into<MessageMedia>) -> Self {
+        self.media = Some(media.into());
+        self
+    }
     pub fn images(mut self, images: impl IntoIterator<Item = &'a str>) -> Self {
         self.images.extend(images);
         self
@@ -45,7 +61,102 @@ impl<'a> MessageBuilder<'a> {
         if let Some(reply_to) = self.reply_to {
             message.msg_id = Some(reply_to);
         }
+        message.event_id = self.reply_to_event;
+        message.media = self.media;
         message.image = self.images.first().copied();
         message
     }
 }
+
+/// 被动回复的场景，不同场景的有效期不同
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyScene {
+    /// 群聊，5 分钟内有效
+    Group,
+    /// 单聊，60 分钟内有效
+    C2c,
+}
+
+impl ReplyScene {
+    /// 被动回复的有效期，超过之后的记录会被清理
+    pub const fn reply_window(self) -> Duration {
+        match self {
+            ReplyScene::Group => Duration::from_secs(5 * 60),
+            ReplyScene::C2c => Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SeqEntry {
+    seq: u32,
+    expire_at: Instant,
+}
+
+/// 为群聊和单聊的被动回复分配 msg_seq，同一条消息（或事件）的每次回复递增
+#[derive(Debug, Default)]
+pub struct MessageSeqCounter {
+    seqs: Mutex<HashMap<String, SeqEntry>>,
+}
+
+impl MessageSeqCounter {
+    /// 为这条消息分配下一个 msg_seq，只有被动回复（带有 msg_id 或 event_id）才需要
+    pub async fn next_for(&self, scene: ReplyScene, message: &MessageSend<'_>) -> Option<u32> {
+        let key = match (&message.msg_id, message.event_id) {
+            (Some(msg_id), _) => msg_id.to_string(),
+            (None, Some(event_id)) => event_id.to_owned(),
+            (None, None) => return None,
+        };
+        let now = Instant::now();
+        let mut seqs = self.seqs.lock().await;
+        seqs.retain(|_, entry| entry.expire_at > now);
+        let entry = seqs.entry(key).or_insert(SeqEntry {
+            seq: 0,
+            expire_at: now + scene.reply_window(),
+        });
+        entry.seq += 1;
+        Some(entry.seq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_msg_seq_per_reply() {
+        let counter = MessageSeqCounter::default();
+        let reply_a = MessageBuilder::default()
+            .reply_to_id("ROBOT1.0_a".parse().expect("valid message id"))
+            .build();
+        let reply_b = MessageBuilder::default()
+            .reply_to_event("GROUP_ADD_ROBOT:b")
+            .build();
+        let group = ReplyScene::Group;
+        assert_eq!(counter.next_for(group, &reply_a).await, Some(1));
+        assert_eq!(counter.next_for(group, &reply_a).await, Some(2));
+        assert_eq!(counter.next_for(group, &reply_b).await, Some(1));
+        assert_eq!(counter.next_for(group, &MessageSend::default()).await, None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_msg_seq_reply_window() {
+        let counter = MessageSeqCounter::default();
+        let group = MessageBuilder::default()
+            .reply_to_id("ROBOT1.0_group".parse().expect("valid message id"))
+            .build();
+        let c2c = MessageBuilder::default()
+            .reply_to_id("ROBOT1.0_c2c".parse().expect("valid message id"))
+            .build();
+        assert_eq!(counter.next_for(ReplyScene::Group, &group).await, Some(1));
+        assert_eq!(counter.next_for(ReplyScene::C2c, &c2c).await, Some(1));
+
+        tokio::time::advance(Duration::from_secs(6 * 60)).await;
+        // the group reply window is over, the c2c one is still open
+        assert_eq!(counter.next_for(ReplyScene::Group, &group).await, Some(1));
+        assert_eq!(counter.next_for(ReplyScene::C2c, &c2c).await, Some(2));
+
+        tokio::time::advance(Duration::from_secs(55 * 60)).await;
+        assert_eq!(counter.next_for(ReplyScene::C2c, &c2c).await, Some(1));
+    }
+}
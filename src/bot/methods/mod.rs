@@ -1,17 +1,36 @@
 use crate::{
     http::api::{
+        audio::{DeleteMic, MicRequest, PostAudio, PostAudioRequest, PutMic},
+        dms::{
+            DeleteDmsMessage, DeleteDmsMessageRequest, PostDms, PostDmsMessage,
+            PostDmsMessageRequest, PostDmsRequest,
+        },
+        forum::{
+            DeleteThread, DeleteThreadRequest, GetThread, GetThreadRequest, GetThreads,
+            GetThreadsRequest, GetThreadsResponse, PutThread, PutThreadRequest,
+        },
         guild::{GetGuild, GetGuildRequest},
-        message::{PostMessage, PostMessageRequest},
+        interaction::{PutInteraction, PutInteractionRequest},
+        media::{
+            PostC2cFile, PostC2cFileRequest, PostGroupFile, PostGroupFileRequest, UploadMediaBody,
+        },
+        message::{
+            PostC2cMessage, PostC2cMessageRequest, PostGroupMessage, PostGroupMessageRequest,
+            PostMessage, PostMessageRequest, PostMessageV2Body,
+        },
         reaction::{
             DeleteEmojiReaction, EmojiReactionDescriptor, GetEmojiReactionUserList,
             GetEmojiReactionUserListRequest, SendEmojiReaction,
         },
         user::GetMe,
     },
-    model::{Guild, MessageSend, User},
+    model::{
+        AudioControl, ChannelId, Dms, Guild, GuildId, InteractionResult, Media, MessageId,
+        MessageSend, MessageSent, Thread, ThreadFormat, ThreadPublished, User,
+    },
 };
 
-use super::*;
+use super::{message::ReplyScene, *};
 
 impl Bot {
     pub fn cache(&self) -> BotCache {
@@ -82,12 +101,149 @@ impl Bot {
         Ok(resp)
     }
 
-    pub async fn about_me(&self) -> Result<crate::model::User, crate::Error> {
+    /// 创建私信会话，`source_guild_id` 是机器人和用户共同所在的频道
+    pub async fn create_dms(
+        &self,
+        recipient_id: &str,
+        source_guild_id: GuildId,
+    ) -> Result<Dms, crate::Error> {
+        self.api_client
+            .send::<PostDms>(&PostDmsRequest {
+                recipient_id,
+                source_guild_id,
+            })
+            .await?
+            .as_result()
+            .map_err(crate::Error::context("create_dms"))
+    }
+
+    /// 发送私信，`guild_id` 为私信会话的 [`Dms::guild_id`]
+    pub async fn send_dms_message(
+        &self,
+        guild_id: GuildId,
+        message: &MessageSend<'_>,
+    ) -> Result<crate::model::MessageBotRecieved, crate::Error> {
+        self.api_client
+            .send::<PostDmsMessage>(&PostDmsMessageRequest::new(guild_id, message))
+            .await?
+            .as_result()
+            .map_err(crate::Error::context("send_dms_message"))
+    }
+
+    /// 撤回私信
+    pub async fn recall_dms_message(
+        &self,
+        guild_id: GuildId,
+        message_id: MessageId,
+        hidetip: bool,
+    ) -> Result<(), crate::Error> {
         self.api_client
+            .send::<DeleteDmsMessage>(&DeleteDmsMessageRequest {
+                guild_id,
+                message_id,
+                hidetip,
+            })
+            .await?
+            .as_result()
+            .map_err(crate::Error::context("recall_dms_message"))?;
+        Ok(())
+    }
+
+    /// 发送群聊消息，被动回复时自动分配 msg_seq
+    pub async fn send_group_message(
+        &self,
+        group_openid: &str,
+        message: &MessageSend<'_>,
+    ) -> Result<MessageSent, crate::Error> {
+        let msg_seq = self.msg_seq.next_for(ReplyScene::Group, message).await;
+        let request = PostGroupMessageRequest {
+            group_openid,
+            body: PostMessageV2Body::new(message, msg_seq),
+        };
+        self.api_client
+            .send::<PostGroupMessage>(&request)
+            .await?
+            .as_result()
+            .map_err(crate::Error::context("send_group_message"))
+    }
+
+    /// 发送单聊消息，被动回复时自动分配 msg_seq
+    pub async fn send_c2c_message(
+        &self,
+        openid: &str,
+        message: &MessageSend<'_>,
+    ) -> Result<MessageSent, crate::Error> {
+        let msg_seq = self.msg_seq.next_for(ReplyScene::C2c, message).await;
+        let request = PostC2cMessageRequest {
+            openid,
+            body: PostMessageV2Body::new(message, msg_seq),
+        };
+        self.api_client
+            .send::<PostC2cMessage>(&request)
+            .await?
+            .as_result()
+            .map_err(crate::Error::context("send_c2c_message"))
+    }
+
+    /// 上传群聊富媒体文件，返回的 [`Media`] 可以作为富媒体消息发送
+    pub async fn upload_group_media(
+        &self,
+        group_openid: &str,
+        media: &UploadMediaBody<'_>,
+    ) -> Result<Media, crate::Error> {
+        self.api_client
+            .send::<PostGroupFile>(&PostGroupFileRequest {
+                group_openid,
+                body: media,
+            })
+            .await?
+            .as_result()
+            .map_err(crate::Error::context("upload_group_media"))
+    }
+
+    /// 上传单聊富媒体文件，返回的 [`Media`] 可以作为富媒体消息发送
+    pub async fn upload_c2c_media(
+        &self,
+        openid: &str,
+        media: &UploadMediaBody<'_>,
+    ) -> Result<Media, crate::Error> {
+        self.api_client
+            .send::<PostC2cFile>(&PostC2cFileRequest {
+                openid,
+                body: media,
+            })
+            .await?
+            .as_result()
+            .map_err(crate::Error::context("upload_c2c_media"))
+    }
+
+    /// 回应互动事件，不回应的话客户端的按钮会一直处于加载状态
+    pub async fn ack_interaction(
+        &self,
+        interaction_id: &str,
+        code: InteractionResult,
+    ) -> Result<(), crate::Error> {
+        self.api_client
+            .send::<PutInteraction>(&PutInteractionRequest {
+                interaction_id,
+                code,
+            })
+            .await?
+            .as_result()
+            .map_err(crate::Error::context("ack_interaction"))?;
+        Ok(())
+    }
+
+    /// 获取机器人自己的信息，同时缓存到 [`BotCache::get_me`](crate::bot::BotCache::get_me)
+    pub async fn about_me(&self) -> Result<crate::model::User, crate::Error> {
+        let me = self
+            .api_client
             .send::<GetMe>(&())
             .await?
             .as_result()
-            .map_err(crate::Error::context("about_me"))
+            .map_err(crate::Error::context("about_me"))?;
+        self.cache.cache_me(me.clone());
+        Ok(me)
     }
 
     pub async fn fetch_my_guilds(&self) -> Result<(), crate::Error> {
@@ -117,6 +273,109 @@ impl Bot {
             .map_err(crate::Error::context("get_guild_from_remote"))
     }
 
+    /// 获取子频道的帖子列表
+    pub async fn list_threads(
+        &self,
+        channel_id: ChannelId,
+    ) -> Result<GetThreadsResponse, crate::Error> {
+        self.api_client
+            .send::<GetThreads>(&GetThreadsRequest { channel_id })
+            .await?
+            .as_result()
+            .map_err(crate::Error::context("list_threads"))
+    }
+
+    /// 获取帖子详情
+    pub async fn get_thread(
+        &self,
+        channel_id: ChannelId,
+        thread_id: &str,
+    ) -> Result<Thread, crate::Error> {
+        self.api_client
+            .send::<GetThread>(&GetThreadRequest {
+                channel_id,
+                thread_id,
+            })
+            .await?
+            .as_result()
+            .map(|resp| resp.thread)
+            .map_err(crate::Error::context("get_thread"))
+    }
+
+    /// 发表帖子，帖子需要经过审核，审核结果通过 `FORUM_PUBLISH_AUDIT_RESULT` 事件推送
+    pub async fn publish_thread(
+        &self,
+        channel_id: ChannelId,
+        title: &str,
+        content: &str,
+        format: ThreadFormat,
+    ) -> Result<ThreadPublished, crate::Error> {
+        self.api_client
+            .send::<PutThread>(&PutThreadRequest {
+                channel_id,
+                title,
+                content,
+                format,
+            })
+            .await?
+            .as_result()
+            .map_err(crate::Error::context("publish_thread"))
+    }
+
+    /// 删除帖子
+    pub async fn delete_thread(
+        &self,
+        channel_id: ChannelId,
+        thread_id: &str,
+    ) -> Result<(), crate::Error> {
+        self.api_client
+            .send::<DeleteThread>(&DeleteThreadRequest {
+                channel_id,
+                thread_id,
+            })
+            .await?
+            .as_result()
+            .map_err(crate::Error::context("delete_thread"))?;
+        Ok(())
+    }
+
+    /// 控制机器人在音频子频道内播放音频
+    pub async fn audio_control(
+        &self,
+        channel_id: ChannelId,
+        control: &AudioControl,
+    ) -> Result<(), crate::Error> {
+        self.api_client
+            .send::<PostAudio>(&PostAudioRequest {
+                channel_id,
+                control,
+            })
+            .await?
+            .as_result()
+            .map_err(crate::Error::context("audio_control"))?;
+        Ok(())
+    }
+
+    /// 机器人在语音子频道上麦
+    pub async fn mic_on(&self, channel_id: ChannelId) -> Result<(), crate::Error> {
+        self.api_client
+            .send::<PutMic>(&MicRequest { channel_id })
+            .await?
+            .as_result()
+            .map_err(crate::Error::context("mic_on"))?;
+        Ok(())
+    }
+
+    /// 机器人在语音子频道下麦
+    pub async fn mic_off(&self, channel_id: ChannelId) -> Result<(), crate::Error> {
+        self.api_client
+            .send::<DeleteMic>(&MicRequest { channel_id })
+            .await?
+            .as_result()
+            .map_err(crate::Error::context("mic_off"))?;
+        Ok(())
+    }
+
     pub async fn create_reaction(
         &self,
         reaction: &EmojiReactionDescriptor,
@@ -52,6 +52,18 @@ impl From<reqwest::Error> for ErrorKind {
     }
 }
 
+impl From<tokio_tungstenite::tungstenite::Error> for ErrorKind {
+    fn from(err: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::WebSocket(Box::new(err))
+    }
+}
+
+impl From<tokio_rustls::rustls::Error> for ErrorKind {
+    fn from(err: tokio_rustls::rustls::Error) -> Self {
+        Self::Tls(err)
+    }
+}
+
 impl From<ResponseFail> for ErrorKind {
     fn from(err: ResponseFail) -> Self {
         Self::ResponseFail(err)
@@ -62,6 +74,8 @@ pub enum ErrorKind {
     SerdeJson(serde_json::Error),
     Io(std::io::Error),
     Reqwest(reqwest::Error),
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
+    Tls(tokio_rustls::rustls::Error),
     ResponseFail(ResponseFail),
     Unexpected,
     Timeout,
@@ -74,6 +88,8 @@ impl std::fmt::Display for ErrorKind {
             Self::SerdeJson(err) => write!(f, "serde_json error: {}", err),
             Self::Io(err) => write!(f, "io error: {}", err),
             Self::Reqwest(err) => write!(f, "reqwest error: {}", err),
+            Self::WebSocket(err) => write!(f, "websocket error: {}", err),
+            Self::Tls(err) => write!(f, "tls error: {}", err),
             Self::Timeout => write!(f, "timeout"),
             Self::ResponseFail(err) => write!(f, "response fail: {}({})", err.message, err.code),
         }
@@ -1,7 +1,10 @@
 use std::{
     borrow::Cow,
     collections::HashMap,
-    sync::{Arc, atomic::AtomicBool},
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
 };
 
 use audit_hook_pool::AuditHookPool;
@@ -9,27 +12,103 @@ use futures_util::{Stream, StreamExt};
 pub mod implement;
 pub mod model;
 use crate::bot::BotRef;
-use handler::EventHandlerId;
-use model::Event;
+use handler::{EventHandlerId, HandlerOptions};
+use implement::replay::EventRecorder;
+use model::{Event, EventEnvelope, Intents};
+use queue::{BoundedQueue, PushError, QueueStats};
 use tokio::sync::RwLock;
 use tokio_util::sync::CancellationToken;
 pub mod audit_hook_pool;
+pub mod custom;
+pub mod dedup;
+pub mod filter;
 pub mod handler;
+pub mod queue;
+pub mod subscribe;
+pub use subscribe::kind;
 // pub trait EventService: Stream<Item = Event> {
 
 // }
 
 pub trait EventStreamProvider:
-    Stream<Item = Event> + Unpin + Sized + Send + Sync + 'static
+    Stream<Item = EventEnvelope> + Unpin + Sized + Send + Sync + 'static
 {
     fn name(&self) -> Cow<'static, str>;
 }
 
+struct HandlerEntry {
+    ct: CancellationToken,
+    intents: Intents,
+    queue: BoundedQueue<EventEnvelope>,
+}
+
+type HandlerMap = Arc<RwLock<HashMap<EventHandlerId, HandlerEntry>>>;
+
+/// 把事件放进每个处理器的队列
+///
+/// 只有 [`OverflowPolicy::Block`](queue::OverflowPolicy::Block) 的处理器队列满时才会等待，多个这样的处理器一起等待
+async fn dispatch(handlers: &HandlerMap, envelope: EventEnvelope, provider_name: &str) {
+    let queues = handlers
+        .read()
+        .await
+        .iter()
+        .map(|(id, entry)| (id.clone(), entry.queue.clone()))
+        .collect::<Vec<_>>();
+    let mut blocked = Vec::new();
+    for (id, queue) in &queues {
+        let stats = queue.stats();
+        let dropped = stats.dropped();
+        match queue.try_push(envelope.clone()) {
+            Ok(()) if stats.dropped() > dropped => {
+                tracing::warn!(
+                    handler = ?id,
+                    dropped = stats.dropped(),
+                    "handler is lagging, dropped the oldest event"
+                );
+            }
+            Ok(()) => {}
+            Err(PushError::Full(envelope)) if queue.policy() == queue::OverflowPolicy::Block => {
+                blocked.push((id, queue, envelope));
+            }
+            Err(PushError::Full(_)) => {
+                tracing::warn!(
+                    handler = ?id,
+                    skipped = stats.rejected(),
+                    event_id = envelope.id,
+                    "handler is lagging, skipped event"
+                );
+            }
+            // the handler is shutting down
+            Err(PushError::Closed(_)) => {}
+        }
+    }
+    if !blocked.is_empty() {
+        tracing::debug!(
+            provider_name,
+            handlers = ?blocked.iter().map(|(id, ..)| *id).collect::<Vec<_>>(),
+            "waiting for blocking handlers"
+        );
+        futures_util::future::join_all(blocked.into_iter().map(
+            |(_, queue, envelope)| async move {
+                // only fails when the handler is shutting down
+                let _ = queue.push(envelope).await;
+            },
+        ))
+        .await;
+    }
+    tracing::debug!(provider_name, handlers = queues.len(), "event dispatched");
+}
+
 pub struct EventService<C: Clone = ()> {
-    running: Arc<AtomicBool>,
+    /// 正在运行的事件流数量
+    running: Arc<AtomicUsize>,
+    /// 多个事件流同时运行时，按 payload id 去重
+    dedup: dedup::DedupCache,
     audit_hook_pool: AuditHookPool,
-    handlers: Arc<RwLock<HashMap<EventHandlerId, CancellationToken>>>,
-    event_dispatch_channel: tokio::sync::broadcast::Sender<Event>,
+    custom_events: custom::CustomEventRegistry,
+    /// 在去重之前记录收到的 payload
+    recorder: Arc<std::sync::RwLock<Option<EventRecorder>>>,
+    handlers: HandlerMap,
     bot: BotRef<C>,
     ct: CancellationToken,
 }
@@ -38,39 +117,48 @@ impl<C: Clone> EventService<C>
 where
     C: Clone + Send + 'static,
 {
-    const DEFAULT_CHANNEL_SIZE: usize = 4096;
+    const DEFAULT_DEDUP_CAPACITY: usize = 4096;
     pub fn is_running(&self) -> bool {
-        self.running.load(std::sync::atomic::Ordering::SeqCst)
+        self.running_providers() > 0
+    }
+    /// 正在运行的事件流数量
+    pub fn running_providers(&self) -> usize {
+        self.running.load(Ordering::SeqCst)
     }
     pub fn new(bot: BotRef<C>) -> Self {
-        let (event_dispatch_channel, _event_subscribe) =
-            tokio::sync::broadcast::channel(Self::DEFAULT_CHANNEL_SIZE);
         Self {
-            running: Arc::new(AtomicBool::new(false)),
+            running: Arc::new(AtomicUsize::new(0)),
+            dedup: dedup::DedupCache::new(Self::DEFAULT_DEDUP_CAPACITY),
             audit_hook_pool: AuditHookPool::new(),
+            custom_events: custom::CustomEventRegistry::new(),
+            recorder: Arc::new(std::sync::RwLock::new(None)),
             handlers: Arc::new(RwLock::new(HashMap::new())),
-            event_dispatch_channel,
             bot,
             ct: CancellationToken::new(),
         }
     }
-    pub(crate) fn spawn<P: EventStreamProvider>(&self, provider: P) -> crate::Result<()> {
+    /// 开始消费一个事件流
+    ///
+    /// 可以同时运行多个事件流，比如 webhook 和 websocket 一起，同一个 payload id 的事件只会分发一次
+    pub fn spawn<P: EventStreamProvider>(&self, provider: P) -> crate::Result<()> {
         let Some(bot) = self.bot.upgrade() else {
             return Err(crate::Error::unexpected("bot dropped"));
         };
-        if self.running.load(std::sync::atomic::Ordering::SeqCst) {
-            return Err(crate::Error::unexpected("event service is running"));
-        }
         {
             let ct = bot.ct.child_token();
-            let event_dispatch_channel = self.event_dispatch_channel.clone();
+            let handlers = self.handlers.clone();
             let audit_hook_pool = self.audit_hook_pool.clone();
             let running = self.running.clone();
+            let dedup = self.dedup.clone();
+            let cache = bot.cache.clone();
+            let custom_events = self.custom_events.clone();
+            let recorder = self.recorder.clone();
+            running.fetch_add(1, Ordering::SeqCst);
             tokio::spawn(async move {
-                let provider_name = provider.name().as_ref().to_string();
+                let provider_name: Arc<str> = Arc::from(provider.name().as_ref());
                 let mut stream = provider;
                 loop {
-                    let event = tokio::select! {
+                    let mut envelope = tokio::select! {
                         _ = ct.cancelled() => {
                             break;
                         },
@@ -82,7 +170,19 @@ where
                             }
                         }
                     };
-                    match &event {
+                    envelope.provider = provider_name.clone();
+                    let current = recorder.read().unwrap_or_else(|e| e.into_inner()).clone();
+                    if let Some(recorder) = current
+                        && let Err(err) = recorder.record(&envelope)
+                    {
+                        tracing::warn!(%provider_name, %err, "failed to record event");
+                    }
+                    if !dedup.insert(&envelope.id) {
+                        tracing::debug!(%provider_name, id = envelope.id, "skip duplicated event");
+                        continue;
+                    }
+                    envelope.event = custom_events.resolve(envelope.event).await;
+                    match &envelope.event {
                         Event::MessageAuditPass(message_audited) => {
                             if let Some(hook) =
                                 audit_hook_pool.remove(&message_audited.audit_id).await
@@ -97,19 +197,20 @@ where
                                 let _send_result = hook.tx.send(message_audited.clone());
                             }
                         }
+                        Event::GuildCreate(_)
+                        | Event::GuildUpdate(_)
+                        | Event::GuildDelete(_)
+                        | Event::ChannelCreate(_)
+                        | Event::ChannelUpdate(_)
+                        | Event::ChannelDelete(_) => {
+                            cache.update_by_event(&envelope.event).await;
+                        }
                         _ => {}
                     }
-                    let Ok(received_count) =
-                        event_dispatch_channel.send(event).inspect_err(|_err| {
-                            tracing::warn!(provider_name, "event dispatch channel send error");
-                        })
-                    else {
-                        break;
-                    };
-                    tracing::debug!(provider_name, received_count, "event dispatched");
+                    dispatch(&handlers, envelope, &provider_name).await;
                 }
-                tracing::info!(provider_name, "event stream ended");
-                running.store(false, std::sync::atomic::Ordering::SeqCst);
+                tracing::info!(%provider_name, "event stream ended");
+                running.fetch_sub(1, Ordering::SeqCst);
             });
         };
         Ok(())
@@ -121,55 +222,154 @@ where
         self.audit_hook_pool.insert(message_id).await
     }
 
+    /// 记录之后所有事件流收到的 payload，包括重复推送的事件，用于 [`ReplayProvider`](implement::replay::ReplayProvider) 重放
+    ///
+    /// 返回之前设置的记录器
+    pub fn set_recorder(&self, recorder: EventRecorder) -> Option<EventRecorder> {
+        self.recorder
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .replace(recorder)
+    }
+
+    /// 停止记录，返回之前设置的记录器
+    pub fn remove_recorder(&self) -> Option<EventRecorder> {
+        self.recorder
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+    }
+
+    /// 自定义事件的注册表，注册之后对应的 [`Event::Unknown`] 会被解析为 [`Event::Custom`]
+    pub fn custom_events(&self) -> &custom::CustomEventRegistry {
+        &self.custom_events
+    }
+
+    /// 使用默认的 [`HandlerOptions`] 启动处理器
     pub async fn spawn_handler<H: handler::EventHandler<C>>(
         &self,
         id: impl Into<EventHandlerId>,
         handler: H,
     ) {
-        let id = id.into();
+        self.spawn_handler_with_options(id, handler, HandlerOptions::default())
+            .await
+    }
+
+    /// 启动处理器，每个处理器有自己的事件队列，处理不过来时按照 [`HandlerOptions::overflow_policy`] 处理
+    ///
+    /// 默认逐个处理事件，可以通过 [`HandlerOptions::max_in_flight`] 和 [`HandlerOptions::ordering_key`] 并发处理
+    pub async fn spawn_handler_with_options<H: handler::EventHandler<C>>(
+        &self,
+        id: impl Into<EventHandlerId>,
+        handler: H,
+        options: HandlerOptions,
+    ) {
+        self.spawn_handler_inner(id.into(), handler, options).await;
+    }
+
+    async fn spawn_handler_inner<H: handler::EventHandler<C>>(
+        &self,
+        id: EventHandlerId,
+        handler: H,
+        options: HandlerOptions,
+    ) -> CancellationToken {
         let ct = self.ct.child_token();
-        let mut rx = self.event_dispatch_channel.subscribe();
+        let queue = BoundedQueue::new(options.queue_size, options.overflow_policy);
         let bot_ref = self.bot.clone();
-        self.handlers.write().await.insert(id.clone(), ct.clone());
+        if let Some(previous) = self.handlers.write().await.insert(
+            id.clone(),
+            HandlerEntry {
+                ct: ct.clone(),
+                intents: handler.intents(),
+                queue: queue.clone(),
+            },
+        ) {
+            previous.ct.cancel();
+        }
+        let handler_ct = ct.clone();
         tokio::spawn(async move {
-            loop {
-                let event = tokio::select! {
-                    _ = ct.cancelled() => {
-                        break;
-                    },
-                    evt = rx.recv()  => {
-                        if let Ok(evt) = evt {
-                            evt
-                        } else {
-                            break;
-                        }
-                    }
-                };
-                let Some(bot) = bot_ref.upgrade() else {
-                    break;
-                };
-                if handler.would_handle(&event, &bot) {
-                    let handle_result = handler.handle(event, &bot).await;
-                    if let Err(err) = handle_result {
-                        tracing::warn!("handler error: {:?}", err);
-                    }
-                }
-                if ct.is_cancelled() {
-                    break;
-                }
-            }
+            handler::runner::supervise(
+                id.clone(),
+                handler,
+                options,
+                queue.clone(),
+                ct.clone(),
+                bot_ref.clone(),
+            )
+            .await;
+            queue.close();
             if let Some(bot) = bot_ref.upgrade() {
-                bot.event_service.handlers.write().await.remove(&id);
+                let mut handlers = bot.event_service.handlers.write().await;
+                // the id may have been taken by a new handler
+                if handlers.get(&id).is_some_and(|entry| entry.ct == ct) {
+                    handlers.remove(&id);
+                }
             }
         });
+        handler_ct
+    }
+
+    /// 订阅某一种事件，直接得到事件的数据
+    ///
+    /// 订阅者来不及读取时丢弃最早的事件，不会阻塞其他处理器，见 [`SubscribeOptions`](subscribe::SubscribeOptions)
+    ///
+    /// ```rust,no_run,ignore
+    /// let mut messages = bot.event_service().subscribe::<kind::AtMessageCreate>().await;
+    /// while let Some(message) = messages.recv().await {
+    ///     // message: Arc<MessageBotRecieved>
+    /// }
+    /// ```
+    pub async fn subscribe<K: subscribe::EventKind>(&self) -> subscribe::Subscription<K::Data> {
+        self.subscribe_with_options::<K>(subscribe::SubscribeOptions::default())
+            .await
+    }
+
+    /// 订阅某一种事件，`options` 用于订阅的队列，转发的处理器使用默认的 [`HandlerOptions`]
+    pub async fn subscribe_with_options<K: subscribe::EventKind>(
+        &self,
+        options: subscribe::SubscribeOptions,
+    ) -> subscribe::Subscription<K::Data> {
+        static SUBSCRIPTION_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        let id = EventHandlerId::new(format!(
+            "subscribe:{}:{}",
+            K::KIND,
+            SUBSCRIPTION_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let queue = BoundedQueue::new(options.queue_size, options.overflow_policy);
+        let forwarder = subscribe::Forwarder::<K> {
+            queue: queue.clone(),
+        };
+        let ct = self
+            .spawn_handler_inner(id, forwarder, HandlerOptions::default())
+            .await;
+        subscribe::Subscription::new(queue, ct.drop_guard())
+    }
+
+    /// 处理器事件队列的计数器，可以用来观察处理器的积压
+    pub async fn handler_stats(&self, id: &EventHandlerId) -> Option<Arc<QueueStats>> {
+        self.handlers
+            .read()
+            .await
+            .get(id)
+            .map(|entry| entry.queue.stats())
     }
 
     pub async fn shutdown_handler(&self, id: &EventHandlerId) {
-        if let Some(ct) = self.handlers.write().await.remove(id) {
-            ct.cancel();
+        if let Some(entry) = self.handlers.write().await.remove(id) {
+            entry.ct.cancel();
+            entry.queue.close();
         }
     }
 
+    /// 所有已注册的处理器需要订阅的事件
+    pub async fn required_intents(&self) -> Intents {
+        self.handlers
+            .read()
+            .await
+            .values()
+            .fold(Intents::empty(), |acc, entry| acc | entry.intents)
+    }
+
     pub async fn shutdown(&self) {
         self.ct.cancel();
     }
@@ -0,0 +1,71 @@
+use std::{collections::HashMap, sync::Arc};
+
+use serde::de::DeserializeOwned;
+use tokio::sync::RwLock;
+
+use super::model::{CustomEvent, Event, UnknownEvent};
+
+type CustomEventParser = Arc<
+    dyn Fn(&serde_json::Value) -> crate::Result<Arc<dyn std::any::Any + Send + Sync>> + Send + Sync,
+>;
+
+/// 自定义事件的注册表
+///
+/// SDK 尚未支持的事件会以 [`Event::Unknown`] 下发，在这里注册事件类型之后，会被解析为 [`Event::Custom`]
+#[derive(Clone, Default)]
+pub struct CustomEventRegistry {
+    parsers: Arc<RwLock<HashMap<String, CustomEventParser>>>,
+}
+
+impl std::fmt::Debug for CustomEventRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomEventRegistry")
+            .finish_non_exhaustive()
+    }
+}
+
+impl CustomEventRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// 注册事件类型 `kind`，事件数据会被解析为 `T`
+    pub async fn register<T>(&self, kind: impl Into<String>)
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        let parser: CustomEventParser = Arc::new(|data| {
+            let data = T::deserialize(data).map_err(crate::Error::context("parse custom event"))?;
+            Ok(Arc::new(data))
+        });
+        self.parsers.write().await.insert(kind.into(), parser);
+    }
+    pub async fn unregister(&self, kind: &str) {
+        self.parsers.write().await.remove(kind);
+    }
+    /// 将已注册的 [`Event::Unknown`] 解析为 [`Event::Custom`]，其他事件原样返回
+    pub async fn resolve(&self, event: Event) -> Event {
+        let Event::Unknown(unknown) = event else {
+            return event;
+        };
+        let Some(parser) = self.parsers.read().await.get(&unknown.kind).cloned() else {
+            return Event::Unknown(unknown);
+        };
+        match parser(&unknown.data) {
+            Ok(data) => Event::Custom(Arc::new(CustomEvent {
+                kind: unknown.kind.clone(),
+                data,
+            })),
+            Err(err) => {
+                tracing::warn!(kind = unknown.kind, %err, "failed to parse custom event");
+                Event::Unknown(unknown)
+            }
+        }
+    }
+}
+
+impl UnknownEvent {
+    /// 尝试将事件数据解析为 `T`
+    pub fn parse<T: DeserializeOwned>(&self) -> crate::Result<T> {
+        T::deserialize(&self.data).map_err(crate::Error::context("parse unknown event"))
+    }
+}
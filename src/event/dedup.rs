@@ -0,0 +1,88 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+/// 有界的去重缓存，记录最近见过的 payload id，超出容量时淘汰最早的记录
+#[derive(Debug, Clone)]
+pub struct DedupCache {
+    capacity: usize,
+    inner: Arc<Mutex<DedupCacheInner>>,
+}
+
+#[derive(Debug, Default)]
+struct DedupCacheInner {
+    order: VecDeque<String>,
+    seen: HashSet<String>,
+}
+
+impl DedupCache {
+    /// `capacity` 为 0 时不做去重
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Arc::new(Mutex::new(DedupCacheInner::default())),
+        }
+    }
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    /// 记录一个 id，如果这个 id 最近已经见过则返回 false
+    pub fn insert(&self, id: &str) -> bool {
+        if self.capacity == 0 || id.is_empty() {
+            return true;
+        }
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if inner.seen.contains(id) {
+            return false;
+        }
+        while inner.order.len() >= self.capacity {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.seen.remove(&oldest);
+            }
+        }
+        inner.order.push_back(id.to_owned());
+        inner.seen.insert(id.to_owned());
+        true
+    }
+    /// 移除一个 id，用于处理失败之后允许重新投递
+    pub fn remove(&self, id: &str) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if inner.seen.remove(id) {
+            inner.order.retain(|seen| seen != id);
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .order
+            .len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_cache() {
+        let cache = DedupCache::new(2);
+        assert!(cache.insert("a"));
+        assert!(!cache.insert("a"));
+        assert!(cache.insert("b"));
+        // "a" is evicted by "c"
+        assert!(cache.insert("c"));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.insert("a"));
+        cache.remove("a");
+        assert!(cache.insert("a"));
+        // a zero capacity cache never reports duplicates
+        let disabled = DedupCache::new(0);
+        assert!(disabled.insert("a"));
+        assert!(disabled.insert("a"));
+    }
+}
@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use crate::{
+    bot::Bot,
+    model::{ChannelId, GuildId, MessageBotRecieved},
+};
+
+use super::model::{Event, EventEnvelope};
+
+/// 事件过滤器，用于实现 [`EventHandler::would_handle`](crate::event::handler::EventHandler::would_handle)
+///
+/// ```rust,no_run,ignore
+/// fn would_handle(&self, event: &EventEnvelope, bot: &Bot) -> bool {
+///     on_message().in_guild(guild_id).not_bot().mentions_me().matches(event, bot)
+/// }
+/// ```
+pub trait EventFilter<C: Clone = ()>: Send + Sync {
+    fn matches(&self, event: &EventEnvelope, bot: &Bot<C>) -> bool;
+}
+
+/// 组合过滤器
+pub trait EventFilterExt: Sized {
+    /// 两个过滤器都通过
+    fn and<F>(self, other: F) -> And<Self, F> {
+        And(self, other)
+    }
+    /// 任意一个过滤器通过
+    fn or<F>(self, other: F) -> Or<Self, F> {
+        Or(self, other)
+    }
+    /// 过滤器不通过
+    fn not(self) -> Not<Self> {
+        Not(self)
+    }
+}
+
+impl<F: Sized> EventFilterExt for F {}
+
+impl<C, F> EventFilter<C> for F
+where
+    C: Clone,
+    F: Fn(&EventEnvelope, &Bot<C>) -> bool + Send + Sync,
+{
+    fn matches(&self, event: &EventEnvelope, bot: &Bot<C>) -> bool {
+        self(event, bot)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct And<A, B>(A, B);
+
+impl<C: Clone, A: EventFilter<C>, B: EventFilter<C>> EventFilter<C> for And<A, B> {
+    fn matches(&self, event: &EventEnvelope, bot: &Bot<C>) -> bool {
+        self.0.matches(event, bot) && self.1.matches(event, bot)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Or<A, B>(A, B);
+
+impl<C: Clone, A: EventFilter<C>, B: EventFilter<C>> EventFilter<C> for Or<A, B> {
+    fn matches(&self, event: &EventEnvelope, bot: &Bot<C>) -> bool {
+        self.0.matches(event, bot) || self.1.matches(event, bot)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Not<A>(A);
+
+impl<C: Clone, A: EventFilter<C>> EventFilter<C> for Not<A> {
+    fn matches(&self, event: &EventEnvelope, bot: &Bot<C>) -> bool {
+        !self.0.matches(event, bot)
+    }
+}
+
+/// 只接受频道消息事件：`MESSAGE_CREATE`、`AT_MESSAGE_CREATE` 和 `DIRECT_MESSAGE_CREATE`
+pub fn on_message() -> MessageFilter {
+    MessageFilter::default()
+}
+
+/// 频道消息的过滤条件，见 [`on_message`]
+#[derive(Debug, Clone, Default)]
+pub struct MessageFilter {
+    guild_id: Option<GuildId>,
+    channel_id: Option<ChannelId>,
+    user_id: Option<u64>,
+    not_bot: bool,
+    mentions_me: bool,
+}
+
+impl MessageFilter {
+    pub fn in_guild(mut self, guild_id: GuildId) -> Self {
+        self.guild_id = Some(guild_id);
+        self
+    }
+    pub fn in_channel(mut self, channel_id: ChannelId) -> Self {
+        self.channel_id = Some(channel_id);
+        self
+    }
+    pub fn from_user(mut self, user_id: u64) -> Self {
+        self.user_id = Some(user_id);
+        self
+    }
+    /// 忽略机器人发出的消息
+    pub fn not_bot(mut self) -> Self {
+        self.not_bot = true;
+        self
+    }
+    /// 只接受@机器人的消息
+    ///
+    /// `AT_MESSAGE_CREATE` 和私信总是通过，`MESSAGE_CREATE` 需要先调用 [`Bot::about_me`] 缓存机器人自己
+    pub fn mentions_me(mut self) -> Self {
+        self.mentions_me = true;
+        self
+    }
+    /// 消息通过时返回消息本身
+    pub fn message<'e, C: Clone>(
+        &self,
+        event: &'e EventEnvelope,
+        bot: &Bot<C>,
+    ) -> Option<&'e Arc<MessageBotRecieved>> {
+        let (message, always_mentioned) = match &event.event {
+            Event::MessageCreate(message) => (message, false),
+            Event::AtMessageCreate(message) | Event::DirectMessageCreate(message) => {
+                (message, true)
+            }
+            _ => return None,
+        };
+        let passed = self.guild_id.is_none_or(|id| message.guild_id == id)
+            && self.channel_id.is_none_or(|id| message.channel_id == id)
+            && self.user_id.is_none_or(|id| message.author.id == id)
+            && !(self.not_bot && message.author.bot)
+            && (!self.mentions_me
+                || always_mentioned
+                || bot
+                    .cache
+                    .get_me()
+                    .is_some_and(|me| message.mentions.contains(&me)));
+        passed.then_some(message)
+    }
+}
+
+impl<C: Clone> EventFilter<C> for MessageFilter {
+    fn matches(&self, event: &EventEnvelope, bot: &Bot<C>) -> bool {
+        self.message(event, bot).is_some()
+    }
+}
@@ -1,6 +1,10 @@
-use super::model::Event;
+use super::{
+    model::{Event, EventEnvelope, Intents},
+    queue::OverflowPolicy,
+};
 pub use crate::bot::Bot;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
+pub(crate) mod runner;
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct EventHandlerId(Arc<str>);
@@ -17,7 +21,200 @@ impl EventHandlerId {
     }
 }
 
+/// 处理器的运行选项
+#[derive(Debug, Clone)]
+pub struct HandlerOptions {
+    /// 处理器事件队列的容量
+    pub queue_size: usize,
+    /// 队列满时的处理方式，默认为 [`OverflowPolicy::DropOldest`] 丢弃最早的事件，[`OverflowPolicy::Reject`] 跳过新的事件，
+    /// 两者都会输出警告；[`OverflowPolicy::Block`] 会让所有事件流等待这个处理器，只适合不能丢事件的处理器
+    pub overflow_policy: OverflowPolicy,
+    /// 同时处理的最大事件数量，为 1 时按顺序逐个处理
+    pub max_in_flight: usize,
+    /// 设置之后，同一个 key 的事件按顺序处理，不同 key 的事件并发处理
+    ///
+    /// 等待同一个 key 前面的事件时不占用 [`max_in_flight`](Self::max_in_flight) 的名额，
+    /// 这样等待的事件最多 [`queue_size`](Self::queue_size) 个，超过之后不再从队列中取事件
+    pub ordering_key: Option<OrderingKey>,
+    /// 单次处理的超时时间，超时之后放弃这次处理并当作错误
+    pub timeout: Option<Duration>,
+    /// 处理器 panic 之后，暂停处理新事件的时间
+    pub restart_backoff: RestartBackoff,
+    /// 处理失败或者 panic 时调用
+    pub on_error: Option<ErrorHook>,
+}
+
+impl HandlerOptions {
+    pub const DEFAULT_QUEUE_SIZE: usize = 4096;
+    pub const DEFAULT_MAX_IN_FLIGHT: usize = 1;
+    pub fn with_queue_size(mut self, queue_size: usize) -> Self {
+        self.queue_size = queue_size;
+        self
+    }
+    pub fn with_overflow_policy(mut self, overflow_policy: OverflowPolicy) -> Self {
+        self.overflow_policy = overflow_policy;
+        self
+    }
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight;
+        self
+    }
+    pub fn with_ordering_key(mut self, ordering_key: OrderingKey) -> Self {
+        self.ordering_key = Some(ordering_key);
+        self
+    }
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+    pub fn with_restart_backoff(mut self, restart_backoff: RestartBackoff) -> Self {
+        self.restart_backoff = restart_backoff;
+        self
+    }
+    pub fn with_on_error<F>(mut self, on_error: F) -> Self
+    where
+        F: Fn(&HandlerFailure) + Send + Sync + 'static,
+    {
+        self.on_error = Some(ErrorHook::new(on_error));
+        self
+    }
+}
+
+impl Default for HandlerOptions {
+    fn default() -> Self {
+        Self {
+            queue_size: Self::DEFAULT_QUEUE_SIZE,
+            overflow_policy: OverflowPolicy::DropOldest,
+            max_in_flight: Self::DEFAULT_MAX_IN_FLIGHT,
+            ordering_key: None,
+            timeout: None,
+            restart_backoff: RestartBackoff::default(),
+            on_error: None,
+        }
+    }
+}
+
+/// 处理器 panic 之后的退避时间，连续 panic 时翻倍，处理成功之后重置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartBackoff {
+    pub initial: Duration,
+    pub max: Duration,
+}
+
+impl RestartBackoff {
+    pub const DEFAULT_INITIAL: Duration = Duration::from_millis(100);
+    pub const DEFAULT_MAX: Duration = Duration::from_secs(30);
+    /// 第 `failures` 次连续失败之后的等待时间
+    pub fn delay(&self, failures: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(failures.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.initial.saturating_mul(factor).min(self.max)
+    }
+}
+
+impl Default for RestartBackoff {
+    fn default() -> Self {
+        Self {
+            initial: Self::DEFAULT_INITIAL,
+            max: Self::DEFAULT_MAX,
+        }
+    }
+}
+
+/// 处理器失败的原因
+#[derive(Debug)]
+pub enum HandlerError {
+    /// `handle` 返回了错误，包括超时
+    Failed(crate::Error),
+    /// `would_handle` 或者 `handle` panic 了，附带 panic 的信息
+    Panicked(String),
+}
+
+impl std::fmt::Display for HandlerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Failed(err) => write!(f, "{}", err),
+            Self::Panicked(message) => write!(f, "handler panicked: {}", message),
+        }
+    }
+}
+
+/// 传给 [`HandlerOptions::on_error`] 的失败信息
+#[derive(Debug)]
+pub struct HandlerFailure {
+    pub handler: EventHandlerId,
+    pub event: EventEnvelope,
+    pub error: HandlerError,
+}
+
+type ErrorHookFn = dyn Fn(&HandlerFailure) + Send + Sync;
+
+/// 处理器失败时的回调，可以用来报警
+#[derive(Clone)]
+pub struct ErrorHook(Arc<ErrorHookFn>);
+
+impl std::fmt::Debug for ErrorHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ErrorHook").finish_non_exhaustive()
+    }
+}
+
+impl ErrorHook {
+    pub fn new<F>(hook: F) -> Self
+    where
+        F: Fn(&HandlerFailure) + Send + Sync + 'static,
+    {
+        Self(Arc::new(hook))
+    }
+    pub fn call(&self, failure: &HandlerFailure) {
+        (self.0)(failure)
+    }
+}
+
+type OrderingKeyFn = dyn Fn(&EventEnvelope) -> Option<String> + Send + Sync;
+
+/// 从事件中取出用于保证顺序的 key，返回 `None` 的事件不保证顺序
+#[derive(Clone)]
+pub struct OrderingKey(Arc<OrderingKeyFn>);
+
+impl std::fmt::Debug for OrderingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("OrderingKey").finish_non_exhaustive()
+    }
+}
+
+impl OrderingKey {
+    pub fn new<F>(key: F) -> Self
+    where
+        F: Fn(&EventEnvelope) -> Option<String> + Send + Sync + 'static,
+    {
+        Self(Arc::new(key))
+    }
+    /// 按照消息所在的子频道、群或者私聊对象排序
+    pub fn channel() -> Self {
+        Self::new(|envelope| match &envelope.event {
+            Event::MessageCreate(message)
+            | Event::AtMessageCreate(message)
+            | Event::DirectMessageCreate(message) => Some(message.channel_id.to_string()),
+            Event::GroupAtMessageCreate(message) => Some(message.group_openid.clone()),
+            Event::C2cMessageCreate(message) => Some(message.author.user_openid.clone()),
+            _ => None,
+        })
+    }
+    pub fn key(&self, envelope: &EventEnvelope) -> Option<String> {
+        (self.0)(envelope)
+    }
+}
+
 pub trait EventHandler<C: Clone = ()>: Send + Sync + 'static {
-    fn would_handle(&self, event: &Event, bot: &Bot<C>) -> bool;
-    fn handle(&self, event: Event, bot: &Bot<C>) -> impl Future<Output = crate::Result<()>> + Send;
+    /// 处理器需要订阅的事件，用于 [`EventService::required_intents`](crate::event::EventService::required_intents)，
+    /// 连接 websocket 时会自动加上
+    fn intents(&self) -> Intents;
+    fn would_handle(&self, event: &EventEnvelope, bot: &Bot<C>) -> bool;
+    fn handle(
+        &self,
+        event: EventEnvelope,
+        bot: &Bot<C>,
+    ) -> impl Future<Output = crate::Result<()>> + Send;
 }
@@ -0,0 +1,313 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    panic::AssertUnwindSafe,
+    sync::{
+        Arc,
+        atomic::{AtomicU32, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use futures_util::FutureExt;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, mpsc};
+use tokio_util::sync::CancellationToken;
+
+use super::{
+    ErrorHook, EventHandler, EventHandlerId, HandlerError, HandlerFailure, HandlerOptions,
+};
+use crate::{
+    bot::{Bot, BotRef},
+    event::{model::EventEnvelope, queue::BoundedQueue},
+};
+
+struct Job<C: Clone> {
+    event: EventEnvelope,
+    bot: Bot<C>,
+    /// 交给 worker 之后到处理完之前占用的缓冲名额
+    _slot: Option<OwnedSemaphorePermit>,
+}
+
+/// 同一个 key 的事件交给同一个 worker 按顺序处理
+struct KeyedWorker<C: Clone> {
+    tx: mpsc::UnboundedSender<Job<C>>,
+    /// 已经交给 worker 但还没处理完的事件数量
+    pending: Arc<AtomicUsize>,
+}
+
+struct Invoker<H> {
+    id: EventHandlerId,
+    handler: Arc<H>,
+    timeout: Option<Duration>,
+    on_error: Option<ErrorHook>,
+    /// 连续 panic 的次数，处理成功之后重置
+    panics: Arc<AtomicU32>,
+}
+
+impl<H> Clone for Invoker<H> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id.clone(),
+            handler: self.handler.clone(),
+            timeout: self.timeout,
+            on_error: self.on_error.clone(),
+            panics: self.panics.clone(),
+        }
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+impl<H> Invoker<H> {
+    async fn invoke<C>(&self, job: Job<C>)
+    where
+        C: Clone + Send + 'static,
+        H: EventHandler<C>,
+    {
+        let Job { event, bot, .. } = job;
+        let reported = self.on_error.is_some().then(|| event.clone());
+        let handle = self.handler.handle(event, &bot);
+        let handle = async {
+            match self.timeout {
+                Some(timeout) => tokio::time::timeout(timeout, handle)
+                    .await
+                    .unwrap_or_else(|_| Err(crate::Error::timeout("handler timed out"))),
+                None => handle.await,
+            }
+        };
+        let error = match AssertUnwindSafe(handle).catch_unwind().await {
+            Ok(Ok(())) => {
+                self.panics.store(0, Ordering::SeqCst);
+                return;
+            }
+            Ok(Err(err)) => HandlerError::Failed(err),
+            Err(panic) => {
+                self.panics.fetch_add(1, Ordering::SeqCst);
+                HandlerError::Panicked(panic_message(panic.as_ref()))
+            }
+        };
+        self.report(reported, error);
+    }
+
+    /// would_handle 中的 panic 同样计入连续 panic 的次数
+    fn would_handle<C>(&self, event: &EventEnvelope, bot: &Bot<C>) -> bool
+    where
+        C: Clone + Send + 'static,
+        H: EventHandler<C>,
+    {
+        match std::panic::catch_unwind(AssertUnwindSafe(|| self.handler.would_handle(event, bot))) {
+            Ok(would_handle) => would_handle,
+            Err(panic) => {
+                self.panics.fetch_add(1, Ordering::SeqCst);
+                let error = HandlerError::Panicked(panic_message(panic.as_ref()));
+                self.report(Some(event.clone()), error);
+                false
+            }
+        }
+    }
+
+    fn report(&self, event: Option<EventEnvelope>, error: HandlerError) {
+        match &error {
+            HandlerError::Failed(err) => {
+                tracing::warn!(handler = ?self.id, "handler error: {:?}", err);
+            }
+            HandlerError::Panicked(message) => {
+                tracing::error!(handler = ?self.id, message, "handler panicked");
+            }
+        }
+        let (Some(on_error), Some(event)) = (&self.on_error, event) else {
+            return;
+        };
+        let failure = HandlerFailure {
+            handler: self.id.clone(),
+            event,
+            error,
+        };
+        if std::panic::catch_unwind(AssertUnwindSafe(|| on_error.call(&failure))).is_err() {
+            tracing::error!(handler = ?self.id, "on_error hook panicked");
+        }
+    }
+}
+
+/// 运行处理器，处理事件的循环本身 panic 时按照 [`RestartBackoff`](super::RestartBackoff) 重启
+pub(crate) async fn supervise<C, H>(
+    id: EventHandlerId,
+    handler: H,
+    options: HandlerOptions,
+    queue: BoundedQueue<EventEnvelope>,
+    ct: CancellationToken,
+    bot_ref: BotRef<C>,
+) where
+    C: Clone + Send + 'static,
+    H: EventHandler<C>,
+{
+    let invoker = Invoker {
+        id,
+        handler: Arc::new(handler),
+        timeout: options.timeout,
+        on_error: options.on_error.clone(),
+        panics: Arc::new(AtomicU32::new(0)),
+    };
+    let mut restarts = 0;
+    loop {
+        let running = run(
+            invoker.clone(),
+            &options,
+            queue.clone(),
+            ct.clone(),
+            bot_ref.clone(),
+        );
+        let Err(panic) = AssertUnwindSafe(running).catch_unwind().await else {
+            break;
+        };
+        restarts += 1;
+        let delay = options.restart_backoff.delay(restarts);
+        tracing::error!(
+            handler = ?invoker.id,
+            message = panic_message(panic.as_ref()),
+            ?delay,
+            "handler loop panicked, restarting"
+        );
+        tokio::select! {
+            _ = ct.cancelled() => {
+                break;
+            },
+            _ = tokio::time::sleep(delay) => {}
+        }
+    }
+}
+
+/// 从处理器的队列中取出事件，按照 [`HandlerOptions`] 的并发设置执行
+async fn run<C, H>(
+    invoker: Invoker<H>,
+    options: &HandlerOptions,
+    queue: BoundedQueue<EventEnvelope>,
+    ct: CancellationToken,
+    bot_ref: BotRef<C>,
+) where
+    C: Clone + Send + 'static,
+    H: EventHandler<C>,
+{
+    let mut backed_off = 0;
+    let semaphore = Arc::new(Semaphore::new(options.max_in_flight.max(1)));
+    // events waiting in keyed workers, bounded so that a slow key can't buffer without limit
+    let backlog = Arc::new(Semaphore::new(options.queue_size.max(1)));
+    let mut workers = HashMap::<String, KeyedWorker<C>>::new();
+    loop {
+        // after a panic, wait before taking the next event
+        let panics = invoker.panics.load(Ordering::SeqCst);
+        if panics > backed_off {
+            tokio::select! {
+                _ = ct.cancelled() => {
+                    break;
+                },
+                _ = tokio::time::sleep(options.restart_backoff.delay(panics)) => {}
+            }
+        }
+        backed_off = panics;
+        let event = tokio::select! {
+            _ = ct.cancelled() => {
+                break;
+            },
+            evt = queue.pop()  => {
+                if let Some(evt) = evt {
+                    evt
+                } else {
+                    break;
+                }
+            }
+        };
+        let Some(bot) = bot_ref.upgrade() else {
+            break;
+        };
+        if !invoker.would_handle(&event, &bot) {
+            continue;
+        }
+        let key = options
+            .ordering_key
+            .as_ref()
+            .and_then(|ordering_key| ordering_key.key(&event));
+        let Some(key) = key else {
+            let Some(permit) = acquire(&semaphore, &ct).await else {
+                break;
+            };
+            let job = Job {
+                event,
+                bot,
+                _slot: None,
+            };
+            let invoker = invoker.clone();
+            let ct = ct.clone();
+            tokio::spawn(async move {
+                let _permit = permit;
+                tokio::select! {
+                    _ = ct.cancelled() => {},
+                    _ = invoker.invoke(job) => {}
+                }
+            });
+            continue;
+        };
+        let Some(slot) = acquire(&backlog, &ct).await else {
+            break;
+        };
+        let job = Job {
+            event,
+            bot,
+            _slot: Some(slot),
+        };
+        if let Some(worker) = workers.get(&key) {
+            worker.pending.fetch_add(1, Ordering::SeqCst);
+            // the worker only exits after we drop the sender
+            let _ = worker.tx.send(job);
+            continue;
+        }
+        // a worker with nothing pending is idle, dropping the sender lets it exit
+        workers.retain(|_, worker| worker.pending.load(Ordering::SeqCst) > 0);
+        let (tx, mut rx) = mpsc::unbounded_channel::<Job<C>>();
+        let pending = Arc::new(AtomicUsize::new(1));
+        let _ = tx.send(job);
+        workers.insert(
+            key,
+            KeyedWorker {
+                tx,
+                pending: pending.clone(),
+            },
+        );
+        let invoker = invoker.clone();
+        let semaphore = semaphore.clone();
+        let ct = ct.clone();
+        tokio::spawn(async move {
+            while let Some(job) = rx.recv().await {
+                // only take an in-flight slot when the job actually starts
+                let Some(_permit) = acquire(&semaphore, &ct).await else {
+                    break;
+                };
+                tokio::select! {
+                    _ = ct.cancelled() => break,
+                    _ = invoker.invoke(job) => {}
+                }
+                pending.fetch_sub(1, Ordering::SeqCst);
+            }
+        });
+    }
+}
+
+/// 等待信号量的名额，处理器停止时返回 `None`
+async fn acquire(
+    semaphore: &Arc<Semaphore>,
+    ct: &CancellationToken,
+) -> Option<OwnedSemaphorePermit> {
+    tokio::select! {
+        _ = ct.cancelled() => None,
+        permit = semaphore.clone().acquire_owned() => permit.ok(),
+    }
+}
@@ -1 +1,3 @@
+pub mod replay;
 pub mod webhook;
+pub mod websocket;
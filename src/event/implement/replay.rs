@@ -0,0 +1,213 @@
+use std::{path::Path, time::Duration};
+
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
+
+use crate::event::{
+    EventStreamProvider,
+    model::{EventEnvelope, GeneralPayload, InboundPayloadKind},
+};
+
+/// 事件记录文件中的一行
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecordedPayload {
+    /// 收到 payload 的时间
+    pub received_at: DateTime<Utc>,
+    /// 事件来源
+    #[serde(default)]
+    pub provider: String,
+    pub payload: GeneralPayload,
+}
+
+/// 将收到的每一个 payload 以 JSONL 的格式写入文件
+///
+/// 通过 [`EventService::set_recorder`](crate::event::EventService::set_recorder) 安装之后，
+/// 事件流收到的 payload 在去重之前按顺序记录，重复推送的事件也会被记录
+///
+/// ```rust,no_run,ignore
+/// bot.event_service()
+///     .set_recorder(EventRecorder::create("events.jsonl").await?);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventRecorder {
+    tx: tokio::sync::mpsc::UnboundedSender<RecorderCommand>,
+}
+
+#[derive(Debug)]
+enum RecorderCommand {
+    Write(Vec<u8>),
+    Flush(tokio::sync::oneshot::Sender<()>),
+}
+
+impl EventRecorder {
+    /// 以追加的方式打开文件，写入在后台进行，不会阻塞事件的接收
+    pub async fn create(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(crate::Error::context("open event record file"))?;
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(Self::write(BufWriter::new(file), rx));
+        Ok(Self { tx })
+    }
+    /// 记录一个事件，没有原始 payload 的事件会被忽略
+    pub fn record(&self, envelope: &EventEnvelope) -> crate::Result<()> {
+        let Some(payload) = envelope.payload() else {
+            return Ok(());
+        };
+        let record = RecordedPayload {
+            received_at: envelope.received_at,
+            provider: envelope.provider.to_string(),
+            payload: payload.clone(),
+        };
+        let mut line =
+            serde_json::to_vec(&record).map_err(crate::Error::context("serialize record"))?;
+        line.push(b'\n');
+        self.tx
+            .send(RecorderCommand::Write(line))
+            .map_err(|_| crate::Error::unexpected("event recorder stopped"))
+    }
+    /// 等待之前记录的事件都写入文件
+    pub async fn flush(&self) -> crate::Result<()> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.tx
+            .send(RecorderCommand::Flush(tx))
+            .map_err(|_| crate::Error::unexpected("event recorder stopped"))?;
+        rx.await
+            .map_err(|_| crate::Error::unexpected("event recorder stopped"))
+    }
+    async fn write(
+        mut writer: BufWriter<tokio::fs::File>,
+        mut rx: tokio::sync::mpsc::UnboundedReceiver<RecorderCommand>,
+    ) {
+        while let Some(command) = rx.recv().await {
+            let result = match command {
+                RecorderCommand::Write(line) => writer.write_all(&line).await,
+                RecorderCommand::Flush(done) => {
+                    let result = writer.flush().await;
+                    let _ = done.send(());
+                    result
+                }
+            };
+            if let Err(err) = result {
+                tracing::warn!(%err, "failed to write event record");
+                continue;
+            }
+            // flush once the backlog is written, so a crash keeps everything before it
+            if rx.is_empty()
+                && let Err(err) = writer.flush().await
+            {
+                tracing::warn!(%err, "failed to flush event record");
+            }
+        }
+        if let Err(err) = writer.flush().await {
+            tracing::warn!(%err, "failed to flush event record");
+        }
+    }
+}
+
+/// 重放的速度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReplaySpeed {
+    /// 按照记录时的时间间隔
+    #[default]
+    Original,
+    /// 尽可能快
+    AsFastAsPossible,
+}
+
+/// 重放 [`EventRecorder`] 记录的文件，可以交给 [`EventService::spawn`](crate::event::EventService::spawn)
+///
+/// 重放的事件保留记录时的 `received_at`
+#[derive(Debug)]
+pub struct ReplayProvider {
+    rx: tokio::sync::mpsc::Receiver<EventEnvelope>,
+}
+
+impl Stream for ReplayProvider {
+    type Item = EventEnvelope;
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl EventStreamProvider for ReplayProvider {
+    fn name(&self) -> std::borrow::Cow<'static, str> {
+        "replay".into()
+    }
+}
+
+impl ReplayProvider {
+    const CHANNEL_SIZE: usize = 1024;
+    pub async fn open(path: impl AsRef<Path>, speed: ReplaySpeed) -> crate::Result<Self> {
+        let file = tokio::fs::File::open(path)
+            .await
+            .map_err(crate::Error::context("open event record file"))?;
+        let (tx, rx) = tokio::sync::mpsc::channel(Self::CHANNEL_SIZE);
+        tokio::spawn(async move {
+            if let Err(err) = Self::replay(BufReader::new(file), speed, tx).await {
+                tracing::warn!(%err, "event replay stopped");
+            }
+        });
+        Ok(Self { rx })
+    }
+
+    async fn replay(
+        reader: BufReader<tokio::fs::File>,
+        speed: ReplaySpeed,
+        tx: tokio::sync::mpsc::Sender<EventEnvelope>,
+    ) -> crate::Result<()> {
+        let mut lines = reader.lines();
+        let mut previous: Option<DateTime<Utc>> = None;
+        let mut line_number = 0;
+        while let Some(line) = lines
+            .next_line()
+            .await
+            .map_err(crate::Error::context("read event record"))?
+        {
+            line_number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = match serde_json::from_str::<RecordedPayload>(&line) {
+                Ok(record) => record,
+                Err(err) => {
+                    tracing::warn!(line_number, %err, "skip invalid event record");
+                    continue;
+                }
+            };
+            if speed == ReplaySpeed::Original {
+                if let Some(previous) = previous {
+                    let delay = (record.received_at - previous)
+                        .to_std()
+                        .unwrap_or(Duration::ZERO);
+                    tokio::time::sleep(delay).await;
+                }
+                previous = Some(record.received_at);
+            }
+            let envelope = match record.payload.into_inbound() {
+                Ok(InboundPayloadKind::Dispatch(envelope)) => envelope,
+                Ok(_) => continue,
+                Err(err) => {
+                    tracing::warn!(line_number, %err, "skip event record");
+                    continue;
+                }
+            };
+            let envelope = EventEnvelope {
+                received_at: record.received_at,
+                ..envelope
+            };
+            if tx.send(envelope).await.is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
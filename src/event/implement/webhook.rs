@@ -1,27 +1,43 @@
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 use axum::Router;
 use futures_util::Stream;
 use tokio_util::sync::CancellationToken;
 
-use crate::event::{EventStreamProvider, model::Event};
+use crate::event::{
+    EventStreamProvider,
+    dedup::DedupCache,
+    model::EventEnvelope,
+    queue::{BoundedQueue, OverflowPolicy, QueueStats},
+};
 mod middleware;
 mod service;
+mod tls;
 mod utils;
 
+pub use tls::{TlsCertSource, WebHookTlsConfig};
+
 #[derive(Debug)]
 pub struct WebHookService {
-    pub(crate) rx: tokio::sync::mpsc::Receiver<Event>,
-    pub(crate) bind: SocketAddr,
+    pub(crate) queue: BoundedQueue<EventEnvelope>,
+    /// 挂载到外部 axum 应用时为空
+    pub(crate) bind: Option<SocketAddr>,
 }
 
 impl Stream for WebHookService {
-    type Item = Event;
+    type Item = EventEnvelope;
     fn poll_next(
-        mut self: std::pin::Pin<&mut Self>,
+        self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
-        self.rx.poll_recv(cx)
+        self.queue.poll_pop(cx)
+    }
+}
+
+impl Drop for WebHookService {
+    fn drop(&mut self) {
+        // nobody consumes the events anymore, fail the following callbacks
+        self.queue.close();
     }
 }
 
@@ -33,13 +49,15 @@ impl EventStreamProvider for WebHookService {
 #[derive(Debug, Clone)]
 pub struct WebHookServiceApp {
     bot_secret: Arc<str>,
-    event_tx: tokio::sync::mpsc::Sender<Event>,
+    event_queue: BoundedQueue<EventEnvelope>,
     config: Arc<WebHookServiceConfig>,
+    dedup: DedupCache,
 }
 
 #[derive(Debug, Clone)]
 pub struct WebHookServiceConfig {
-    body_size_limit: u32,
+    body_size_limit: usize,
+    timestamp_tolerance: Option<Duration>,
 }
 
 impl WebHookServiceApp {
@@ -51,39 +69,112 @@ impl WebHookServiceApp {
                 app.clone(),
                 middleware::signature_check,
             ))
+            .layer(axum::extract::DefaultBodyLimit::max(
+                app.config.body_size_limit,
+            ))
             .with_state(app);
         Ok(router)
     }
 }
 
-pub struct WebHookServiceAppConfig {
-    pub bind: SocketAddr,
+/// webhook 路由的配置
+#[derive(Debug, Clone)]
+pub struct WebHookRouterConfig {
+    /// 事件队列的容量
     pub channel_size: usize,
+    /// 事件队列满时的处理方式，默认为 [`OverflowPolicy::Reject`]，回调返回 503，QQ 会稍后重新推送
+    pub overflow_policy: OverflowPolicy,
+    /// 请求体的最大字节数
     pub max_body_size: usize,
+    /// `X-Signature-Timestamp` 与本地时间允许的最大偏差，为空时不检查
+    pub timestamp_tolerance: Option<Duration>,
+    /// 记录最近多少个 payload id 用于去重，为 0 时不去重
+    pub dedup_capacity: usize,
+}
+
+impl WebHookRouterConfig {
+    pub const DEFAULT_CHANNEL_SIZE: usize = 4096;
+    // 16 MB
+    pub const DEFAULT_BODY_SIZE: usize = 16 * 1024 * 1024;
+    pub const DEFAULT_TIMESTAMP_TOLERANCE: Duration = Duration::from_secs(5 * 60);
+    pub const DEFAULT_DEDUP_CAPACITY: usize = 4096;
+}
+
+impl Default for WebHookRouterConfig {
+    fn default() -> Self {
+        Self {
+            channel_size: Self::DEFAULT_CHANNEL_SIZE,
+            overflow_policy: OverflowPolicy::Reject,
+            max_body_size: Self::DEFAULT_BODY_SIZE,
+            timestamp_tolerance: Some(Self::DEFAULT_TIMESTAMP_TOLERANCE),
+            dedup_capacity: Self::DEFAULT_DEDUP_CAPACITY,
+        }
+    }
+}
+
+pub struct WebHookServiceAppConfig {
+    pub bind: SocketAddr,
+    pub router: WebHookRouterConfig,
+    /// 为空时监听 http，否则直接提供 https 服务
+    pub tls: Option<WebHookTlsConfig>,
+}
+
+impl WebHookServiceAppConfig {
+    /// 使用默认配置
+    pub fn new(bind: SocketAddr) -> Self {
+        Self {
+            bind,
+            router: WebHookRouterConfig::default(),
+            tls: None,
+        }
+    }
+    pub fn with_tls(mut self, tls: WebHookTlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
 }
 
 impl WebHookService {
-    pub fn get_bind(&self) -> SocketAddr {
+    /// 独立监听时的地址，挂载到外部 axum 应用时为空
+    pub fn get_bind(&self) -> Option<SocketAddr> {
         self.bind
     }
-    pub async fn run(
-        config: WebHookServiceAppConfig,
-        secret: &str,
-        ct: CancellationToken,
-    ) -> crate::Result<Self> {
-        let (tx, rx) = tokio::sync::mpsc::channel(config.channel_size);
+    /// 事件队列的计数器
+    pub fn stats(&self) -> Arc<QueueStats> {
+        self.queue.stats()
+    }
+    /// 创建 webhook 服务，但不监听端口
+    ///
+    /// 返回的路由在 `/` 处理回调，可以通过 `Router::nest_service` 挂载到已有 axum 应用的任意路径下
+    pub fn mount(config: WebHookRouterConfig, secret: &str) -> crate::Result<(Self, Router)> {
+        let queue = BoundedQueue::new(config.channel_size, config.overflow_policy);
         let app = WebHookServiceApp {
             bot_secret: Arc::from(secret),
-            event_tx: tx,
+            event_queue: queue.clone(),
             config: Arc::new(WebHookServiceConfig {
-                body_size_limit: 1024 * 1024,
+                body_size_limit: config.max_body_size,
+                timestamp_tolerance: config.timestamp_tolerance,
             }),
+            dedup: DedupCache::new(config.dedup_capacity),
         };
-        let app = Arc::new(app);
+        let router = app.build_service()?;
+        Ok((Self { queue, bind: None }, router))
+    }
+    pub async fn run(
+        config: WebHookServiceAppConfig,
+        secret: &str,
+        ct: CancellationToken,
+    ) -> crate::Result<Self> {
+        let (mut webhook, service) = Self::mount(config.router, secret)?;
         let tokio_tcp_listen = tokio::net::TcpListener::bind(config.bind)
             .await
             .map_err(crate::Error::context("failed to bind to address"))?;
-        let service = app.build_service()?;
+        webhook.bind = Some(config.bind);
+        if let Some(tls) = config.tls {
+            let acceptor = tls::build_acceptor(tls, ct.clone()).await?;
+            tokio::spawn(tls::serve(tokio_tcp_listen, acceptor, service, ct));
+            return Ok(webhook);
+        }
         tokio::spawn(async move {
             let result = axum::serve(tokio_tcp_listen, service)
                 .with_graceful_shutdown(async move {
@@ -95,9 +186,6 @@ impl WebHookService {
                 tracing::error!("webhook service error: {:?}", err);
             }
         });
-        Ok(Self {
-            rx,
-            bind: config.bind,
-        })
+        Ok(webhook)
     }
 }
@@ -31,8 +31,20 @@ pub async fn signature_check(
         .get(HEADER_TIMESTAMP)
         .ok_or(StatusCode::UNAUTHORIZED)?
         .clone();
+    if let Some(tolerance) = app.config.timestamp_tolerance {
+        let timestamp: i64 = timestamp
+            .to_str()
+            .ok()
+            .and_then(|timestamp| timestamp.parse().ok())
+            .ok_or(StatusCode::BAD_REQUEST)?;
+        let now = chrono::Utc::now().timestamp();
+        if !crate::utils::verify_timestamp(timestamp, now, tolerance.as_secs()) {
+            tracing::warn!(timestamp, now, "webhook request timestamp out of window");
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+    }
     let (parts, body) = request.into_parts();
-    let body = axum::body::to_bytes(body, app.config.body_size_limit as usize)
+    let body = axum::body::to_bytes(body, app.config.body_size_limit)
         .await
         .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE)?;
     let mut message = Vec::with_capacity(timestamp.len() + body.len());
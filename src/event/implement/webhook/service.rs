@@ -2,7 +2,7 @@ use axum::{Json, extract::State};
 use http::StatusCode;
 use serde_json::Value as JsonValue;
 
-use crate::event::{implement::webhook::WebHookServiceApp, model::*};
+use crate::event::{implement::webhook::WebHookServiceApp, model::*, queue::PushError};
 pub async fn event_listen_service(
     State(app): State<WebHookServiceApp>,
     Json(payload): Json<GeneralPayload>,
@@ -15,14 +15,29 @@ pub async fn event_listen_service(
 
     match inbound {
         InboundPayloadKind::Dispatch(event) => {
-            tracing::info!(?event, "event inbound");
-            let result = app.event_tx.send(event).await;
+            let ack = serde_json::to_value(ack).expect("ack should be a valid json");
+            // QQ re-delivers the callback when the ack is slow, ack the duplicate without dispatching
+            if !app.dedup.insert(&event.id) {
+                tracing::debug!(id = event.id, "duplicated event");
+                return Ok(Json(ack));
+            }
+            tracing::info!(id = event.id, event = ?event.event, "event inbound");
+            let id = event.id.clone();
+            let result = app.event_queue.push(event).await;
             match result {
-                Ok(_) => Ok(Json(
-                    serde_json::to_value(ack).expect("ack should be a valid json"),
-                )),
-                Err(e) => {
-                    tracing::error!(%e, "failed to dispatch event");
+                Ok(_) => Ok(Json(ack)),
+                Err(PushError::Full(_)) => {
+                    tracing::warn!(
+                        id,
+                        rejected = app.event_queue.stats().rejected(),
+                        "event queue is full, reject event"
+                    );
+                    app.dedup.remove(&id);
+                    Err(StatusCode::SERVICE_UNAVAILABLE)
+                }
+                Err(PushError::Closed(_)) => {
+                    tracing::error!(id, "failed to dispatch event, event queue closed");
+                    app.dedup.remove(&id);
                     Err(StatusCode::INTERNAL_SERVER_ERROR)
                 }
             }
@@ -35,5 +50,6 @@ pub async fn event_listen_service(
             StatusCode::INTERNAL_SERVER_ERROR
         })
         .map(Json),
+        _ => Err(StatusCode::UNSUPPORTED_MEDIA_TYPE),
     }
 }
@@ -0,0 +1,322 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use axum::Router;
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::conn::auto,
+    service::TowerToHyperService,
+};
+use tokio_rustls::{
+    TlsAcceptor,
+    rustls::{
+        self, ServerConfig,
+        pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+        server::{ClientHello, ResolvesServerCert},
+        sign::CertifiedKey,
+    },
+};
+use tokio_util::sync::CancellationToken;
+
+/// 证书来源
+#[derive(Debug, Clone)]
+pub enum TlsCertSource {
+    /// PEM 格式的证书链和私钥文件，文件变化之后会自动重新加载
+    PemFiles { cert: PathBuf, key: PathBuf },
+    /// 内存中 PEM 格式的证书链和私钥
+    Pem { cert: Vec<u8>, key: Vec<u8> },
+}
+
+#[derive(Debug, Clone)]
+pub struct WebHookTlsConfig {
+    pub source: TlsCertSource,
+    /// 检查证书文件是否变化的间隔
+    pub reload_interval: Duration,
+}
+
+impl WebHookTlsConfig {
+    pub const DEFAULT_RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+    pub fn from_pem_files(cert: impl Into<PathBuf>, key: impl Into<PathBuf>) -> Self {
+        Self {
+            source: TlsCertSource::PemFiles {
+                cert: cert.into(),
+                key: key.into(),
+            },
+            reload_interval: Self::DEFAULT_RELOAD_INTERVAL,
+        }
+    }
+    pub fn from_pem(cert: impl Into<Vec<u8>>, key: impl Into<Vec<u8>>) -> Self {
+        Self {
+            source: TlsCertSource::Pem {
+                cert: cert.into(),
+                key: key.into(),
+            },
+            reload_interval: Self::DEFAULT_RELOAD_INTERVAL,
+        }
+    }
+}
+
+/// 总是返回当前证书，重新加载时替换
+#[derive(Debug)]
+struct ReloadableCertResolver {
+    current: RwLock<Arc<CertifiedKey>>,
+}
+
+impl ReloadableCertResolver {
+    fn current(&self) -> Arc<CertifiedKey> {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+impl ResolvesServerCert for ReloadableCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current())
+    }
+}
+
+fn pem_error(err: rustls::pki_types::pem::Error) -> crate::Error {
+    crate::Error::new(rustls::Error::General(err.to_string()).into(), "parse pem")
+}
+
+fn certified_key(cert: &[u8], key: &[u8]) -> crate::Result<Arc<CertifiedKey>> {
+    let certs = CertificateDer::pem_slice_iter(cert)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(pem_error)?;
+    if certs.is_empty() {
+        return Err(crate::Error::unexpected("no certificate found in pem"));
+    }
+    let key = PrivateKeyDer::from_pem_slice(key).map_err(pem_error)?;
+    let signing_key = rustls::crypto::ring::sign::any_supported_type(&key)
+        .map_err(crate::Error::context("load private key"))?;
+    let certified_key = CertifiedKey::new(certs, signing_key);
+    // the files may be replaced one by one, never serve a mismatched pair
+    certified_key.keys_match().map_err(crate::Error::context(
+        "certificate and private key mismatch",
+    ))?;
+    Ok(Arc::new(certified_key))
+}
+
+async fn load(source: &TlsCertSource) -> crate::Result<Arc<CertifiedKey>> {
+    match source {
+        TlsCertSource::PemFiles { cert, key } => {
+            let cert = tokio::fs::read(cert)
+                .await
+                .map_err(crate::Error::context("read certificate file"))?;
+            let key = tokio::fs::read(key)
+                .await
+                .map_err(crate::Error::context("read private key file"))?;
+            certified_key(&cert, &key)
+        }
+        TlsCertSource::Pem { cert, key } => certified_key(cert, key),
+    }
+}
+
+async fn modified(source: &TlsCertSource) -> Option<(SystemTime, SystemTime)> {
+    let TlsCertSource::PemFiles { cert, key } = source else {
+        return None;
+    };
+    let cert = tokio::fs::metadata(cert).await.ok()?.modified().ok()?;
+    let key = tokio::fs::metadata(key).await.ok()?.modified().ok()?;
+    Some((cert, key))
+}
+
+async fn watch(
+    resolver: Arc<ReloadableCertResolver>,
+    config: WebHookTlsConfig,
+    mut last_modified: Option<(SystemTime, SystemTime)>,
+    ct: CancellationToken,
+) {
+    let mut interval = tokio::time::interval(config.reload_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = ct.cancelled() => break,
+            _ = interval.tick() => {}
+        }
+        let current = modified(&config.source).await;
+        if current.is_none() || current == last_modified {
+            continue;
+        }
+        match load(&config.source).await {
+            Ok(certified_key) => {
+                *resolver.current.write().unwrap_or_else(|e| e.into_inner()) = certified_key;
+                last_modified = current;
+                tracing::info!("webhook certificate reloaded");
+            }
+            Err(err) => {
+                tracing::warn!(%err, "failed to reload webhook certificate, keep the previous one")
+            }
+        }
+    }
+}
+
+/// 加载证书，如果证书来自文件，则在后台定期检查并重新加载
+async fn reloadable_resolver(
+    config: WebHookTlsConfig,
+    ct: CancellationToken,
+) -> crate::Result<Arc<ReloadableCertResolver>> {
+    // taken before loading, so a replacement right after the load is still picked up
+    let last_modified = modified(&config.source).await;
+    let resolver = Arc::new(ReloadableCertResolver {
+        current: RwLock::new(load(&config.source).await?),
+    });
+    if matches!(config.source, TlsCertSource::PemFiles { .. }) {
+        tokio::spawn(watch(resolver.clone(), config, last_modified, ct));
+    }
+    Ok(resolver)
+}
+
+pub(crate) async fn build_acceptor(
+    config: WebHookTlsConfig,
+    ct: CancellationToken,
+) -> crate::Result<TlsAcceptor> {
+    let resolver = reloadable_resolver(config, ct).await?;
+    let mut server_config =
+        ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(crate::Error::context("build tls config"))?
+            .with_no_client_auth()
+            .with_cert_resolver(resolver);
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_millis(100);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub(crate) async fn serve(
+    listener: tokio::net::TcpListener,
+    acceptor: TlsAcceptor,
+    router: Router,
+    ct: CancellationToken,
+) {
+    loop {
+        let (stream, remote) = tokio::select! {
+            _ = ct.cancelled() => break,
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(err) => {
+                    // errors like EMFILE persist for a while, don't spin on them
+                    tracing::warn!(%err, "failed to accept webhook connection");
+                    tokio::time::sleep(ACCEPT_ERROR_DELAY).await;
+                    continue;
+                }
+            }
+        };
+        let acceptor = acceptor.clone();
+        let router = router.clone();
+        let ct = ct.clone();
+        tokio::spawn(async move {
+            let stream =
+                match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => stream,
+                    Ok(Err(err)) => {
+                        tracing::debug!(%remote, %err, "tls handshake failed");
+                        return;
+                    }
+                    Err(_) => {
+                        tracing::debug!(%remote, "tls handshake timeout");
+                        return;
+                    }
+                };
+            let builder = auto::Builder::new(TokioExecutor::new());
+            let connection = builder.serve_connection_with_upgrades(
+                TokioIo::new(stream),
+                TowerToHyperService::new(router),
+            );
+            tokio::pin!(connection);
+            let result = tokio::select! {
+                result = connection.as_mut() => result,
+                _ = ct.cancelled() => {
+                    connection.as_mut().graceful_shutdown();
+                    connection.await
+                }
+            };
+            if let Err(err) = result {
+                tracing::debug!(%remote, %err, "webhook connection error");
+            }
+        });
+    }
+    tracing::info!("webhook service shutdown");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_certified_key() {
+        let cert = include_bytes!("testdata/cert.pem");
+        let key = include_bytes!("testdata/key.pem");
+        let other_key = include_bytes!("testdata/other_key.pem");
+        assert!(certified_key(cert, key).is_ok());
+        assert!(certified_key(cert, other_key).is_err());
+        assert!(certified_key(b"", key).is_err());
+    }
+
+    /// 等待后台任务加载到 `cert` 对应的证书
+    async fn wait_for_cert(resolver: &ReloadableCertResolver, cert: &[u8]) -> bool {
+        let expected = CertificateDer::from_pem_slice(cert).expect("parse test certificate");
+        for _ in 0..100 {
+            if resolver.current().cert[0] == expected {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn test_reload_cert_files() {
+        let cert = include_bytes!("testdata/cert.pem");
+        let key = include_bytes!("testdata/key.pem");
+        let other_cert = include_bytes!("testdata/other_cert.pem");
+        let other_key = include_bytes!("testdata/other_key.pem");
+
+        let dir = std::env::temp_dir().join(format!("qqbot-sdk-tls-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+        // bump mtime explicitly, a rewrite within the same tick may keep the old one
+        let mut generation = SystemTime::now();
+        let mut write = |cert: &[u8], key: &[u8]| {
+            generation += Duration::from_secs(1);
+            for (path, content) in [(&cert_path, cert), (&key_path, key)] {
+                std::fs::write(path, content).expect("write test pem");
+                std::fs::File::options()
+                    .write(true)
+                    .open(path)
+                    .and_then(|file| file.set_modified(generation))
+                    .expect("set mtime");
+            }
+        };
+        write(cert, key);
+
+        let ct = CancellationToken::new();
+        let config = WebHookTlsConfig {
+            reload_interval: Duration::from_millis(10),
+            ..WebHookTlsConfig::from_pem_files(&cert_path, &key_path)
+        };
+        let resolver = reloadable_resolver(config, ct.clone())
+            .await
+            .expect("load certificate");
+        assert!(wait_for_cert(&resolver, cert).await);
+
+        write(other_cert, other_key);
+        assert!(wait_for_cert(&resolver, other_cert).await);
+
+        // a mismatched pair is rejected, the previous certificate stays in use
+        write(cert, other_key);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(wait_for_cert(&resolver, other_cert).await);
+
+        ct.cancel();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
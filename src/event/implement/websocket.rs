@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use futures_util::Stream;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    event::{
+        EventStreamProvider,
+        model::{EventEnvelope, Intents},
+    },
+    http::{api::websocket::GatewayBot, client::reqwest_client::ApiClient},
+};
+mod connection;
+mod session;
+mod shard;
+
+pub(crate) use connection::GatewayConnection;
+pub use session::*;
+pub use shard::ShardManager;
+
+#[derive(Debug)]
+pub struct WebSocketService {
+    pub(crate) rx: tokio::sync::mpsc::Receiver<EventEnvelope>,
+    pub(crate) url: String,
+    pub(crate) shard_manager: ShardManager,
+}
+
+impl Stream for WebSocketService {
+    type Item = EventEnvelope;
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl EventStreamProvider for WebSocketService {
+    fn name(&self) -> std::borrow::Cow<'static, str> {
+        "tungstenite-websocket".into()
+    }
+}
+
+pub struct WebSocketServiceConfig {
+    /// 订阅的事件
+    pub intents: Intents,
+    /// 分片数量，为空时使用 `/gateway/bot` 建议的分片数
+    pub shards: Option<u32>,
+    pub channel_size: usize,
+    /// 会话存储，重启之后优先尝试 Resume
+    pub session_store: Arc<dyn SessionStore>,
+}
+
+impl WebSocketService {
+    pub fn get_url(&self) -> &str {
+        &self.url
+    }
+    pub fn shard_manager(&self) -> &ShardManager {
+        &self.shard_manager
+    }
+    pub async fn run(
+        config: WebSocketServiceConfig,
+        api_client: ApiClient,
+        ct: CancellationToken,
+    ) -> crate::Result<Self> {
+        let gateway = api_client
+            .send::<GatewayBot>(&())
+            .await?
+            .as_result()
+            .map_err(crate::Error::context("get gateway"))?;
+        let (tx, rx) = tokio::sync::mpsc::channel(config.channel_size);
+        let shard_manager = ShardManager::new(
+            gateway.url.clone(),
+            api_client,
+            config.intents,
+            config.shards.unwrap_or(gateway.shards),
+            tx,
+            config.session_store,
+            ct,
+        );
+        {
+            let shard_manager = shard_manager.clone();
+            let limit = gateway.session_start_limit;
+            tokio::spawn(async move { shard_manager.start_all(limit).await });
+        }
+        Ok(Self {
+            rx,
+            url: gateway.url,
+            shard_manager,
+        })
+    }
+}
@@ -0,0 +1,279 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use futures_util::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::{
+    Message,
+    protocol::{CloseFrame, frame::coding::CloseCode},
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    event::model::{EventEnvelope, GeneralPayload, Identify, InboundPayloadKind, Intents, Resume},
+    http::client::reqwest_client::ApiClient,
+};
+
+use super::{GatewaySession, SessionStore, shard::IdentifyGate};
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// 主动关闭时发送 Close 的超时时间，连接可能已经半开
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// 断开之后应当如何继续
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reconnect {
+    /// 使用原有的 session 恢复连接
+    Resume,
+    /// 丢弃原有的 session 重新鉴权
+    Identify,
+    /// 不再重连
+    Stop,
+}
+
+impl Reconnect {
+    /// 参考 https://bot.q.qq.com/wiki/develop/api-v2/dev-prepare/error-trace/websocket.html
+    fn from_close_code(code: u16) -> Self {
+        match code {
+            // 无效的 session id / seq 错误 / 鉴权失败
+            4004 | 4006 | 4007 => Reconnect::Identify,
+            // shard、version、intent 错误，机器人已下架、已封禁
+            4010..=4014 | 4914 | 4915 => Reconnect::Stop,
+            _ => Reconnect::Resume,
+        }
+    }
+}
+
+pub(crate) struct GatewayConnection {
+    url: String,
+    api_client: ApiClient,
+    intents: Intents,
+    shard: Option<[u32; 2]>,
+    tx: tokio::sync::mpsc::Sender<EventEnvelope>,
+    session_store: Arc<dyn SessionStore>,
+    identify_gate: IdentifyGate,
+    session_id: Option<String>,
+    seq: Option<u32>,
+}
+
+impl GatewayConnection {
+    pub(crate) fn new(
+        url: String,
+        api_client: ApiClient,
+        intents: Intents,
+        shard: Option<[u32; 2]>,
+        tx: tokio::sync::mpsc::Sender<EventEnvelope>,
+        session_store: Arc<dyn SessionStore>,
+        identify_gate: IdentifyGate,
+    ) -> Self {
+        Self {
+            url,
+            api_client,
+            intents,
+            shard,
+            tx,
+            session_store,
+            identify_gate,
+            session_id: None,
+            seq: None,
+        }
+    }
+
+    pub(crate) async fn run(mut self, ct: CancellationToken) {
+        match self.session_store.load(self.shard).await {
+            Ok(Some(session)) => {
+                tracing::info!(shard = ?self.shard, session_id = session.session_id, seq = session.seq, "resuming stored session");
+                self.session_id = Some(session.session_id);
+                self.seq = Some(session.seq);
+            }
+            Ok(None) => {}
+            Err(err) => tracing::warn!(shard = ?self.shard, %err, "failed to load session"),
+        }
+        loop {
+            let reconnect = match self.connect(&ct).await {
+                Ok(reconnect) => reconnect,
+                Err(err) => {
+                    tracing::warn!(shard = ?self.shard, %err, "gateway connection error");
+                    Reconnect::Resume
+                }
+            };
+            match reconnect {
+                Reconnect::Stop => break,
+                Reconnect::Identify => {
+                    self.session_id = None;
+                    self.seq = None;
+                    if let Err(err) = self.session_store.remove(self.shard).await {
+                        tracing::warn!(shard = ?self.shard, %err, "failed to remove session");
+                    }
+                }
+                Reconnect::Resume => {}
+            }
+            tokio::select! {
+                _ = ct.cancelled() => break,
+                _ = tokio::time::sleep(RECONNECT_DELAY) => {}
+            }
+        }
+        self.save_session().await;
+        tracing::info!(shard = ?self.shard, "gateway connection closed");
+        // mark this connection as finished for the shard manager
+        ct.cancel();
+    }
+
+    async fn connect(&mut self, ct: &CancellationToken) -> crate::Result<Reconnect> {
+        // without a session this connection identifies, wait for the limit before connecting
+        // so that the identify can be sent right after hello
+        if self.session_id.is_none() && !self.identify_gate.acquire(ct).await {
+            return Ok(Reconnect::Stop);
+        }
+        let (ws, _) = tokio::select! {
+            _ = ct.cancelled() => return Ok(Reconnect::Stop),
+            ws = tokio_tungstenite::connect_async(self.url.as_str()) => {
+                ws.map_err(crate::Error::context("connect to gateway"))?
+            }
+        };
+        let (mut sink, mut stream) = ws.split();
+        let mut heartbeat_interval: Option<tokio::time::Interval> = None;
+        // a heartbeat is sent but not acked yet
+        let mut awaiting_ack = false;
+        loop {
+            let message = tokio::select! {
+                _ = ct.cancelled() => {
+                    // a normal close (1000/1001) invalidates the session, keep it for resuming later
+                    let close = sink.send(Message::Close(Some(CloseFrame {
+                        code: CloseCode::Restart,
+                        reason: "resume later".into(),
+                    })));
+                    if tokio::time::timeout(CLOSE_TIMEOUT, close).await.is_err() {
+                        tracing::debug!(shard = ?self.shard, "send close frame timeout");
+                    }
+                    return Ok(Reconnect::Stop);
+                },
+                _ = async {
+                    match heartbeat_interval.as_mut() {
+                        Some(interval) => interval.tick().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    if awaiting_ack {
+                        // no ack within a whole heartbeat interval, the connection is probably half-open
+                        tracing::warn!(shard = ?self.shard, "heartbeat ack timeout");
+                        return Ok(Reconnect::Resume);
+                    }
+                    let heartbeat = GeneralPayload::new_heartbeat(self.seq);
+                    sink.send(to_message(&heartbeat)?)
+                        .await
+                        .map_err(crate::Error::context("send heartbeat"))?;
+                    awaiting_ack = true;
+                    self.save_session().await;
+                    continue;
+                },
+                message = stream.next() => message,
+            };
+            let text = match message {
+                None => return Ok(Reconnect::Resume),
+                Some(Err(err)) => {
+                    return Err(crate::Error::new(err.into(), "receive from gateway"));
+                }
+                Some(Ok(Message::Text(text))) => text,
+                Some(Ok(Message::Close(frame))) => {
+                    let code = frame.map(|frame| frame.code).unwrap_or(CloseCode::Normal);
+                    tracing::info!(shard = ?self.shard, %code, "gateway closed by remote");
+                    return Ok(Reconnect::from_close_code(code.into()));
+                }
+                Some(Ok(_)) => continue,
+            };
+            let payload: GeneralPayload = match serde_json::from_str(text.as_str()) {
+                Ok(payload) => payload,
+                Err(err) => {
+                    tracing::warn!(%err, "invalid gateway payload");
+                    continue;
+                }
+            };
+            if let Some(seq) = payload.seq {
+                self.seq = Some(seq);
+            }
+            let inbound = match payload.into_inbound() {
+                Ok(inbound) => inbound,
+                Err(err) => {
+                    tracing::debug!(%err, "failed to convert payload to inbound payload");
+                    continue;
+                }
+            };
+            match inbound {
+                InboundPayloadKind::Hello(hello) => {
+                    let period = Duration::from_millis(hello.heartbeat_interval);
+                    heartbeat_interval = Some(tokio::time::interval_at(
+                        tokio::time::Instant::now() + period,
+                        period,
+                    ));
+                    let token = self.api_client.auth_header().await?;
+                    let token = token
+                        .to_str()
+                        .map_err(|_| crate::Error::unexpected("invalid auth header"))?
+                        .to_owned();
+                    let payload = if let Some(session_id) = self.session_id.clone() {
+                        GeneralPayload::new_resume(&Resume {
+                            token,
+                            session_id,
+                            seq: self.seq.unwrap_or_default(),
+                        })?
+                    } else {
+                        GeneralPayload::new_identify(&Identify {
+                            token,
+                            intents: self.intents,
+                            shard: self.shard,
+                            properties: HashMap::new(),
+                        })?
+                    };
+                    sink.send(to_message(&payload)?)
+                        .await
+                        .map_err(crate::Error::context("send identify"))?;
+                }
+                InboundPayloadKind::Ready(ready) => {
+                    tracing::info!(shard = ?ready.shard, session_id = ready.session_id, "gateway ready");
+                    self.session_id = Some(ready.session_id);
+                    self.save_session().await;
+                }
+                InboundPayloadKind::Resumed => {
+                    tracing::info!(shard = ?self.shard, "gateway resumed");
+                }
+                InboundPayloadKind::Dispatch(event) => {
+                    if self.tx.send(event).await.is_err() {
+                        tracing::warn!("event receiver dropped");
+                        return Ok(Reconnect::Stop);
+                    }
+                }
+                InboundPayloadKind::HeartbeatAck => {
+                    awaiting_ack = false;
+                    tracing::trace!(shard = ?self.shard, "heartbeat ack");
+                }
+                InboundPayloadKind::Reconnect => return Ok(Reconnect::Resume),
+                InboundPayloadKind::InvalidSession => return Ok(Reconnect::Identify),
+                InboundPayloadKind::HttpCallbackValidation(_) => {
+                    tracing::warn!("unexpected http callback validation from gateway");
+                }
+            }
+        }
+    }
+}
+
+impl GatewayConnection {
+    /// 持久化当前的会话，在 Ready、每次心跳和连接关闭时调用
+    async fn save_session(&self) {
+        let Some(session_id) = self.session_id.clone() else {
+            return;
+        };
+        let session = GatewaySession {
+            session_id,
+            seq: self.seq.unwrap_or_default(),
+            shard: self.shard,
+        };
+        if let Err(err) = self.session_store.save(&session).await {
+            tracing::warn!(shard = ?self.shard, %err, "failed to save session");
+        }
+    }
+}
+
+fn to_message(payload: &GeneralPayload) -> crate::Result<Message> {
+    let text =
+        serde_json::to_string(payload).map_err(crate::Error::context("serializing payload"))?;
+    Ok(Message::Text(text.into()))
+}
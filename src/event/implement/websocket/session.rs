@@ -0,0 +1,159 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, RwLock};
+
+/// 用于 Resume 的 gateway 会话状态
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GatewaySession {
+    pub session_id: String,
+    /// 最后收到的消息序号
+    pub seq: u32,
+    /// 分片信息 `[shard_id, shard_count]`
+    pub shard: Option<[u32; 2]>,
+}
+
+/// 保存 gateway 会话状态，用于进程重启之后恢复连接
+#[async_trait::async_trait]
+pub trait SessionStore: Send + Sync + 'static {
+    async fn load(&self, shard: Option<[u32; 2]>) -> crate::Result<Option<GatewaySession>>;
+    async fn save(&self, session: &GatewaySession) -> crate::Result<()>;
+    async fn remove(&self, shard: Option<[u32; 2]>) -> crate::Result<()>;
+}
+
+fn shard_key(shard: Option<[u32; 2]>) -> String {
+    match shard {
+        Some([shard_id, shard_count]) => format!("{shard_id}/{shard_count}"),
+        None => "default".to_owned(),
+    }
+}
+
+/// 仅在内存中保存，进程重启后失效
+#[derive(Debug, Default)]
+pub struct MemorySessionStore {
+    sessions: RwLock<HashMap<String, GatewaySession>>,
+}
+
+impl MemorySessionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl SessionStore for MemorySessionStore {
+    async fn load(&self, shard: Option<[u32; 2]>) -> crate::Result<Option<GatewaySession>> {
+        Ok(self.sessions.read().await.get(&shard_key(shard)).cloned())
+    }
+    async fn save(&self, session: &GatewaySession) -> crate::Result<()> {
+        self.sessions
+            .write()
+            .await
+            .insert(shard_key(session.shard), session.clone());
+        Ok(())
+    }
+    async fn remove(&self, shard: Option<[u32; 2]>) -> crate::Result<()> {
+        self.sessions.write().await.remove(&shard_key(shard));
+        Ok(())
+    }
+}
+
+/// 以 json 文件保存所有分片的会话
+#[derive(Debug)]
+pub struct JsonFileSessionStore {
+    path: PathBuf,
+    sessions: Mutex<Option<HashMap<String, GatewaySession>>>,
+}
+
+impl JsonFileSessionStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            sessions: Mutex::new(None),
+        }
+    }
+
+    async fn read_file(&self) -> crate::Result<HashMap<String, GatewaySession>> {
+        match tokio::fs::read(&self.path).await {
+            Ok(content) => serde_json::from_slice(&content)
+                .map_err(crate::Error::context("parse session store file")),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(err) => Err(crate::Error::new(err.into(), "read session store file")),
+        }
+    }
+
+    async fn write_file(&self, sessions: &HashMap<String, GatewaySession>) -> crate::Result<()> {
+        let content = serde_json::to_vec_pretty(sessions)
+            .map_err(crate::Error::context("serialize session store"))?;
+        // write to a temporary file first, so a crash never leaves a half written file
+        let tmp_path = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, content)
+            .await
+            .map_err(crate::Error::context("write session store file"))?;
+        tokio::fs::rename(&tmp_path, &self.path)
+            .await
+            .map_err(crate::Error::context("replace session store file"))
+    }
+
+    async fn update(
+        &self,
+        f: impl FnOnce(&mut HashMap<String, GatewaySession>),
+    ) -> crate::Result<()> {
+        let mut sessions = self.sessions.lock().await;
+        if sessions.is_none() {
+            *sessions = Some(self.read_file().await?);
+        }
+        let sessions = sessions.get_or_insert_with(HashMap::new);
+        f(sessions);
+        self.write_file(sessions).await
+    }
+}
+
+#[async_trait::async_trait]
+impl SessionStore for JsonFileSessionStore {
+    async fn load(&self, shard: Option<[u32; 2]>) -> crate::Result<Option<GatewaySession>> {
+        let mut sessions = self.sessions.lock().await;
+        if sessions.is_none() {
+            *sessions = Some(self.read_file().await?);
+        }
+        Ok(sessions
+            .as_ref()
+            .and_then(|sessions| sessions.get(&shard_key(shard)).cloned()))
+    }
+    async fn save(&self, session: &GatewaySession) -> crate::Result<()> {
+        self.update(|sessions| {
+            sessions.insert(shard_key(session.shard), session.clone());
+        })
+        .await
+    }
+    async fn remove(&self, shard: Option<[u32; 2]>) -> crate::Result<()> {
+        self.update(|sessions| {
+            sessions.remove(&shard_key(shard));
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_json_file_session_store() -> crate::Result<()> {
+        let path = std::env::temp_dir().join(format!("qqbot-session-{}.json", std::process::id()));
+        let session = GatewaySession {
+            session_id: "082ee18c-0be3-491b-9d8b-fbd95c51673a".to_owned(),
+            seq: 42,
+            shard: Some([1, 2]),
+        };
+        JsonFileSessionStore::new(&path).save(&session).await?;
+        // a new store reads what the previous process left behind
+        let store = JsonFileSessionStore::new(&path);
+        assert_eq!(store.load(Some([1, 2])).await?, Some(session));
+        assert_eq!(store.load(Some([0, 2])).await?, None);
+        store.remove(Some([1, 2])).await?;
+        assert_eq!(store.load(Some([1, 2])).await?, None);
+        let _ = std::fs::remove_file(&path);
+        Ok(())
+    }
+}
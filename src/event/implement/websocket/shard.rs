@@ -0,0 +1,280 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use tokio::{
+    sync::{Mutex, RwLock},
+    time::Instant,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    event::model::{EventEnvelope, Intents},
+    http::{api::websocket::SessionStartLimit, client::reqwest_client::ApiClient},
+};
+
+use super::{GatewayConnection, SessionStore};
+
+/// 每一批 `max_concurrency` 个分片之间的鉴权间隔
+const IDENTIFY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 管理同一个 gateway 下的多个分片连接，所有分片的事件汇入同一个通道
+#[derive(Clone)]
+pub struct ShardManager {
+    inner: Arc<ShardManagerInner>,
+}
+
+struct ShardManagerInner {
+    url: String,
+    api_client: ApiClient,
+    intents: Intents,
+    shard_count: u32,
+    tx: tokio::sync::mpsc::Sender<EventEnvelope>,
+    session_store: Arc<dyn SessionStore>,
+    shards: RwLock<HashMap<u32, ShardHandle>>,
+    identify_gate: IdentifyGate,
+    ct: CancellationToken,
+}
+
+struct ShardHandle {
+    ct: CancellationToken,
+    /// 连接关闭并保存会话之后结束
+    task: tokio::task::JoinHandle<()>,
+}
+
+/// 所有分片共享的鉴权限制，每次发送 Identify 之前都要经过这里，Resume 不需要
+#[derive(Clone, Default)]
+pub(crate) struct IdentifyGate {
+    /// `start_all` 之前为空，不做限制
+    limiter: Arc<Mutex<Option<IdentifyLimiter>>>,
+}
+
+impl IdentifyGate {
+    async fn install(&self, limit: SessionStartLimit) {
+        *self.limiter.lock().await = Some(IdentifyLimiter::new(limit));
+    }
+    /// 等待鉴权的名额，等待期间被取消则返回 false
+    pub(crate) async fn acquire(&self, ct: &CancellationToken) -> bool {
+        // hold the lock while waiting, so shards identify one after another
+        let mut limiter = self.limiter.lock().await;
+        let Some(limiter) = limiter.as_mut() else {
+            return true;
+        };
+        while let Some(slot) = limiter.next_slot() {
+            if limiter.remaining == 0 {
+                tracing::warn!(
+                    reset_after = limiter.limit.reset_after,
+                    "session start limit reached, waiting for reset"
+                );
+            }
+            tokio::select! {
+                _ = ct.cancelled() => return false,
+                _ = tokio::time::sleep_until(slot) => {}
+            }
+        }
+        limiter.consume();
+        true
+    }
+}
+
+/// 按照 `session_start_limit` 限制分片的鉴权
+struct IdentifyLimiter {
+    limit: SessionStartLimit,
+    remaining: u32,
+    /// `remaining` 恢复为 `total` 的时间
+    reset_at: Instant,
+    /// 当前这一批已经启动的分片数量和开始时间
+    bucket: Option<(u32, Instant)>,
+}
+
+impl IdentifyLimiter {
+    fn new(limit: SessionStartLimit) -> Self {
+        Self {
+            remaining: limit.remaining,
+            reset_at: Instant::now() + Duration::from_millis(limit.reset_after.into()),
+            bucket: None,
+            limit,
+        }
+    }
+    /// 下一次鉴权之前需要等到的时间，为空时可以立即鉴权
+    fn next_slot(&mut self) -> Option<Instant> {
+        let now = Instant::now();
+        if let Some((started, since)) = self.bucket
+            && started >= self.limit.max_concurrency.max(1)
+        {
+            if now < since + IDENTIFY_INTERVAL {
+                return Some(since + IDENTIFY_INTERVAL);
+            }
+            self.bucket = None;
+        }
+        if self.remaining == 0 {
+            if now < self.reset_at {
+                return Some(self.reset_at);
+            }
+            self.remaining = self.limit.total;
+            self.reset_at = now + Duration::from_millis(self.limit.reset_after.into());
+        }
+        None
+    }
+    fn consume(&mut self) {
+        self.remaining = self.remaining.saturating_sub(1);
+        let (started, _) = self.bucket.get_or_insert((0, Instant::now()));
+        *started += 1;
+    }
+}
+
+impl std::fmt::Debug for ShardManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShardManager")
+            .field("url", &self.inner.url)
+            .field("shard_count", &self.inner.shard_count)
+            .finish()
+    }
+}
+
+impl ShardManager {
+    pub(crate) fn new(
+        url: String,
+        api_client: ApiClient,
+        intents: Intents,
+        shard_count: u32,
+        tx: tokio::sync::mpsc::Sender<EventEnvelope>,
+        session_store: Arc<dyn SessionStore>,
+        ct: CancellationToken,
+    ) -> Self {
+        Self {
+            inner: Arc::new(ShardManagerInner {
+                url,
+                api_client,
+                intents,
+                shard_count: shard_count.max(1),
+                tx,
+                session_store,
+                shards: RwLock::new(HashMap::new()),
+                identify_gate: IdentifyGate::default(),
+                ct,
+            }),
+        }
+    }
+
+    pub fn shard_count(&self) -> u32 {
+        self.inner.shard_count
+    }
+
+    /// 当前正在运行的分片
+    pub async fn running_shards(&self) -> Vec<u32> {
+        let mut shards = self
+            .inner
+            .shards
+            .read()
+            .await
+            .iter()
+            .filter(|(_, shard)| !shard.ct.is_cancelled())
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        shards.sort_unstable();
+        shards
+    }
+
+    /// 启动所有分片，鉴权按照 `session_start_limit` 的限制分批进行
+    pub(crate) async fn start_all(&self, limit: SessionStartLimit) {
+        self.inner.identify_gate.install(limit).await;
+        for shard_id in 0..self.inner.shard_count {
+            self.spawn_shard(shard_id).await;
+        }
+    }
+
+    /// 重启一个分片，等待原有的连接关闭并保存会话之后再启动新的连接
+    pub async fn restart_shard(&self, shard_id: u32) -> crate::Result<()> {
+        if shard_id >= self.inner.shard_count {
+            return Err(crate::Error::unexpected(format!(
+                "shard {shard_id} out of range {}",
+                self.inner.shard_count
+            )));
+        }
+        self.stop_shard(shard_id).await;
+        self.spawn_shard(shard_id).await;
+        Ok(())
+    }
+
+    /// 关闭一个分片，等待连接关闭并保存会话
+    pub async fn stop_shard(&self, shard_id: u32) {
+        let Some(shard) = self.inner.shards.write().await.remove(&shard_id) else {
+            return;
+        };
+        shard.ct.cancel();
+        if let Err(err) = shard.task.await {
+            tracing::warn!(shard_id, %err, "shard connection task failed");
+        }
+    }
+
+    /// 启动分片，需要鉴权时由连接自己等待 [`IdentifyGate`]
+    async fn spawn_shard(&self, shard_id: u32) {
+        let ct = self.inner.ct.child_token();
+        let connection = GatewayConnection::new(
+            self.inner.url.clone(),
+            self.inner.api_client.clone(),
+            self.inner.intents,
+            Some([shard_id, self.inner.shard_count]),
+            self.inner.tx.clone(),
+            self.inner.session_store.clone(),
+            self.inner.identify_gate.clone(),
+        );
+        tracing::info!(
+            shard_id,
+            shard_count = self.inner.shard_count,
+            "starting shard"
+        );
+        let task = tokio::spawn(connection.run(ct.clone()));
+        if let Some(previous) = self
+            .inner
+            .shards
+            .write()
+            .await
+            .insert(shard_id, ShardHandle { ct, task })
+        {
+            previous.ct.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_identify_limiter() {
+        let gate = IdentifyGate::default();
+        gate.install(SessionStartLimit {
+            total: 10,
+            remaining: 3,
+            reset_after: 60_000,
+            max_concurrency: 2,
+        })
+        .await;
+        let ct = CancellationToken::new();
+        let start = Instant::now();
+        let mut identified = Vec::new();
+        for _ in 0..6 {
+            assert!(gate.acquire(&ct).await);
+            identified.push(start.elapsed());
+        }
+        assert_eq!(
+            identified,
+            [
+                // the first batch of `max_concurrency`
+                Duration::ZERO,
+                Duration::ZERO,
+                // the next batch waits for the interval
+                IDENTIFY_INTERVAL,
+                // `remaining` is used up, wait for `reset_after`
+                Duration::from_secs(60),
+                // a new batch right after the reset
+                Duration::from_secs(60),
+                Duration::from_secs(60),
+            ]
+        );
+
+        // cancelled while waiting for the next batch
+        ct.cancel();
+        assert!(!gate.acquire(&ct).await);
+    }
+}
@@ -4,13 +4,24 @@ use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use crate::model::*;
+mod envelope;
+mod intents;
 mod payload;
+pub use envelope::*;
+pub use intents::*;
 pub use payload::*;
 
 #[derive(Serialize_repr, Deserialize_repr, Clone, Debug, PartialEq, Eq, Copy)]
 #[repr(u8)]
 pub enum Opcode {
     Dispatch = 0,
+    Heartbeat = 1,
+    Identify = 2,
+    Resume = 6,
+    Reconnect = 7,
+    InvalidSession = 9,
+    Hello = 10,
+    HeartbeatAck = 11,
     // #[serde(rename = "HTTP Callback ACK")]
     HttpCallbackAck = 12,
     HttpCallbackValidation = 13,
@@ -20,6 +31,13 @@ impl std::fmt::Display for Opcode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Opcode::Dispatch => write!(f, "`Dispatch`({})", *self as u8),
+            Opcode::Heartbeat => write!(f, "`Heartbeat`({})", *self as u8),
+            Opcode::Identify => write!(f, "`Identify`({})", *self as u8),
+            Opcode::Resume => write!(f, "`Resume`({})", *self as u8),
+            Opcode::Reconnect => write!(f, "`Reconnect`({})", *self as u8),
+            Opcode::InvalidSession => write!(f, "`Invalid Session`({})", *self as u8),
+            Opcode::Hello => write!(f, "`Hello`({})", *self as u8),
+            Opcode::HeartbeatAck => write!(f, "`Heartbeat ACK`({})", *self as u8),
             Opcode::HttpCallbackAck => write!(f, "`HTTP Callback ACK`({})", *self as u8),
             Opcode::HttpCallbackValidation => {
                 write!(f, "`HTTP Callback Validation`{}", *self as u8)
@@ -28,20 +46,184 @@ impl std::fmt::Display for Opcode {
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
-#[serde(tag = "kind", content = "data", rename_all = "SCREAMING_SNAKE_CASE")]
-#[non_exhaustive]
-pub enum Event {
-    MessageCreate(Arc<MessageBotRecieved>),
-    MessageDelete(Arc<MessageDeleted>),
-    PublicMessageDelete(Arc<MessageDeleted>),
-    AtMessageCreate(Arc<MessageBotRecieved>),
-    MessageAuditPass(Arc<MessageAudited>),
-    MessageAuditReject(Arc<MessageAudited>),
-    MessageReactionAdd(Arc<MessageReaction>),
-    MessageReactionRemove(Arc<MessageReaction>),
-    #[serde(other)]
-    Unknown,
+/// SDK 支持的所有事件，`Event` 的变体和 [`kind`](crate::event::kind) 都由这份列表生成
+macro_rules! with_event_kinds {
+    ($callback:ident) => {
+        $callback! {
+            GuildCreate("GUILD_CREATE") => GuildWithOpUser,
+            GuildUpdate("GUILD_UPDATE") => GuildWithOpUser,
+            GuildDelete("GUILD_DELETE") => GuildWithOpUser,
+            ChannelCreate("CHANNEL_CREATE") => ChannelWithOpUser,
+            ChannelUpdate("CHANNEL_UPDATE") => ChannelWithOpUser,
+            ChannelDelete("CHANNEL_DELETE") => ChannelWithOpUser,
+            GuildMemberAdd("GUILD_MEMBER_ADD") => MemberWithGuildID,
+            GuildMemberUpdate("GUILD_MEMBER_UPDATE") => MemberWithGuildID,
+            GuildMemberRemove("GUILD_MEMBER_REMOVE") => MemberWithGuildID,
+            MessageCreate("MESSAGE_CREATE") => MessageBotRecieved,
+            MessageDelete("MESSAGE_DELETE") => MessageDeleted,
+            PublicMessageDelete("PUBLIC_MESSAGE_DELETE") => MessageDeleted,
+            AtMessageCreate("AT_MESSAGE_CREATE") => MessageBotRecieved,
+            MessageAuditPass("MESSAGE_AUDIT_PASS") => MessageAudited,
+            MessageAuditReject("MESSAGE_AUDIT_REJECT") => MessageAudited,
+            MessageReactionAdd("MESSAGE_REACTION_ADD") => MessageReaction,
+            MessageReactionRemove("MESSAGE_REACTION_REMOVE") => MessageReaction,
+            DirectMessageCreate("DIRECT_MESSAGE_CREATE") => MessageBotRecieved,
+            DirectMessageDelete("DIRECT_MESSAGE_DELETE") => MessageDeleted,
+            ForumThreadCreate("FORUM_THREAD_CREATE") => Thread,
+            ForumThreadUpdate("FORUM_THREAD_UPDATE") => Thread,
+            ForumThreadDelete("FORUM_THREAD_DELETE") => Thread,
+            ForumPostCreate("FORUM_POST_CREATE") => Post,
+            ForumPostDelete("FORUM_POST_DELETE") => Post,
+            ForumReplyCreate("FORUM_REPLY_CREATE") => Reply,
+            ForumReplyDelete("FORUM_REPLY_DELETE") => Reply,
+            ForumPublishAuditResult("FORUM_PUBLISH_AUDIT_RESULT") => ForumAuditResult,
+            AudioStart("AUDIO_START") => AudioAction,
+            AudioFinish("AUDIO_FINISH") => AudioAction,
+            AudioOnMic("AUDIO_ON_MIC") => AudioAction,
+            AudioOffMic("AUDIO_OFF_MIC") => AudioAction,
+            AudioOrLiveChannelMemberEnter("AUDIO_OR_LIVE_CHANNEL_MEMBER_ENTER") => AudioLiveChannelMember,
+            AudioOrLiveChannelMemberExit("AUDIO_OR_LIVE_CHANNEL_MEMBER_EXIT") => AudioLiveChannelMember,
+            GroupAtMessageCreate("GROUP_AT_MESSAGE_CREATE") => GroupMessageRecieved,
+            C2cMessageCreate("C2C_MESSAGE_CREATE") => C2cMessageRecieved,
+            GroupAddRobot("GROUP_ADD_ROBOT") => GroupOperation,
+            GroupDelRobot("GROUP_DEL_ROBOT") => GroupOperation,
+            GroupMsgReceive("GROUP_MSG_RECEIVE") => GroupOperation,
+            GroupMsgReject("GROUP_MSG_REJECT") => GroupOperation,
+            FriendAdd("FRIEND_ADD") => FriendOperation,
+            FriendDel("FRIEND_DEL") => FriendOperation,
+            C2cMsgReceive("C2C_MSG_RECEIVE") => FriendOperation,
+            C2cMsgReject("C2C_MSG_REJECT") => FriendOperation,
+            InteractionCreate("INTERACTION_CREATE") => Interaction,
+        }
+    };
+}
+pub(crate) use with_event_kinds;
+
+macro_rules! define_event {
+    ($($variant:ident($kind:literal) => $data:ty),* $(,)?) => {
+        #[derive(Deserialize, Clone, Debug)]
+        #[serde(tag = "kind", content = "data")]
+        #[non_exhaustive]
+        pub enum Event {
+            $(
+                #[serde(rename = $kind)]
+                $variant(Arc<$data>),
+            )*
+            /// SDK 尚未支持的事件，保留原始的事件类型和数据
+            #[serde(skip)]
+            Unknown(Arc<UnknownEvent>),
+            /// 通过 [`CustomEventRegistry`](crate::event::custom::CustomEventRegistry) 注册的自定义事件
+            #[serde(skip)]
+            Custom(Arc<CustomEvent>),
+        }
+
+        impl Event {
+            /// SDK 支持的所有事件类型
+            pub const KINDS: &[&str] = &[$($kind),*];
+
+            /// 事件类型，即 payload 中的 `t` 字段
+            pub fn kind(&self) -> &str {
+                match self {
+                    $(Event::$variant(_) => $kind,)*
+                    Event::Unknown(unknown) => &unknown.kind,
+                    Event::Custom(custom) => &custom.kind,
+                }
+            }
+        }
+    };
+}
+
+with_event_kinds!(define_event);
+
+impl Event {
+    /// 是否是 SDK 支持的事件类型，不支持的事件会以 [`Event::Unknown`] 下发
+    pub fn is_known_kind(kind: &str) -> bool {
+        Self::KINDS.contains(&kind)
+    }
+
+    /// 接收这个事件所需要的 intents
+    pub fn intents(&self) -> Intents {
+        match self {
+            Event::GuildCreate(_)
+            | Event::GuildUpdate(_)
+            | Event::GuildDelete(_)
+            | Event::ChannelCreate(_)
+            | Event::ChannelUpdate(_)
+            | Event::ChannelDelete(_) => Intents::GUILDS,
+            Event::GuildMemberAdd(_)
+            | Event::GuildMemberUpdate(_)
+            | Event::GuildMemberRemove(_) => Intents::GUILD_MEMBERS,
+            Event::MessageCreate(_) | Event::MessageDelete(_) => Intents::GUILD_MESSAGES,
+            Event::PublicMessageDelete(_) | Event::AtMessageCreate(_) => {
+                Intents::PUBLIC_GUILD_MESSAGES
+            }
+            Event::MessageAuditPass(_) | Event::MessageAuditReject(_) => Intents::MESSAGE_AUDIT,
+            Event::MessageReactionAdd(_) | Event::MessageReactionRemove(_) => {
+                Intents::GUILD_MESSAGE_REACTIONS
+            }
+            Event::DirectMessageCreate(_) | Event::DirectMessageDelete(_) => {
+                Intents::DIRECT_MESSAGE
+            }
+            Event::ForumThreadCreate(_)
+            | Event::ForumThreadUpdate(_)
+            | Event::ForumThreadDelete(_)
+            | Event::ForumPostCreate(_)
+            | Event::ForumPostDelete(_)
+            | Event::ForumReplyCreate(_)
+            | Event::ForumReplyDelete(_)
+            | Event::ForumPublishAuditResult(_) => Intents::FORUMS_EVENT,
+            Event::AudioStart(_)
+            | Event::AudioFinish(_)
+            | Event::AudioOnMic(_)
+            | Event::AudioOffMic(_) => Intents::AUDIO_ACTION,
+            Event::AudioOrLiveChannelMemberEnter(_) | Event::AudioOrLiveChannelMemberExit(_) => {
+                Intents::AUDIO_OR_LIVE_CHANNEL_MEMBER
+            }
+            Event::GroupAtMessageCreate(_)
+            | Event::C2cMessageCreate(_)
+            | Event::GroupAddRobot(_)
+            | Event::GroupDelRobot(_)
+            | Event::GroupMsgReceive(_)
+            | Event::GroupMsgReject(_)
+            | Event::FriendAdd(_)
+            | Event::FriendDel(_)
+            | Event::C2cMsgReceive(_)
+            | Event::C2cMsgReject(_) => Intents::GROUP_AND_C2C_EVENT,
+            Event::InteractionCreate(_) => Intents::INTERACTION,
+            Event::Unknown(unknown) => Intents::from_event_type(&unknown.kind).unwrap_or_default(),
+            Event::Custom(custom) => Intents::from_event_type(&custom.kind).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UnknownEvent {
+    /// 事件类型，即 payload 中的 `t` 字段
+    pub kind: String,
+    /// 事件数据，即 payload 中的 `d` 字段
+    pub data: serde_json::Value,
+}
+
+#[derive(Clone)]
+pub struct CustomEvent {
+    /// 事件类型，即 payload 中的 `t` 字段
+    pub kind: String,
+    pub(crate) data: Arc<dyn std::any::Any + Send + Sync>,
+}
+
+impl CustomEvent {
+    /// 获取注册时指定类型的事件数据
+    pub fn downcast_ref<T: std::any::Any>(&self) -> Option<&T> {
+        self.data.downcast_ref()
+    }
+}
+
+impl std::fmt::Debug for CustomEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomEvent")
+            .field("kind", &self.kind)
+            .finish_non_exhaustive()
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -55,12 +237,18 @@ pub struct Ready {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Identify {
     pub token: String,
-    pub intents: u32,
+    pub intents: Intents,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub shard: Option<[u32; 2]>,
     pub properties: HashMap<String, String>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Hello {
+    /// 心跳周期，单位毫秒
+    pub heartbeat_interval: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Resume {
     pub token: String,
@@ -0,0 +1,57 @@
+use std::{ops::Deref, sync::Arc};
+
+use chrono::{DateTime, Utc};
+
+use super::{Event, GeneralPayload};
+
+/// 下发给处理器的事件，附带 payload 中的元数据
+#[derive(Clone, Debug)]
+pub struct EventEnvelope {
+    /// payload 中的 `id`，即事件 id，可以用于被动回复非消息事件
+    pub id: String,
+    /// payload 中的 `s`，webhook 下发的事件可能没有
+    pub seq: Option<u32>,
+    /// 事件来源的 [`EventStreamProvider`](crate::event::EventStreamProvider) 名称，由 [`EventService`](crate::event::EventService) 填写
+    pub provider: Arc<str>,
+    /// 收到 payload 的时间
+    pub received_at: DateTime<Utc>,
+    pub event: Event,
+    /// 原始的 payload，用于记录和重放
+    pub(crate) payload: Option<Arc<GeneralPayload>>,
+}
+
+impl EventEnvelope {
+    pub fn new(id: String, seq: Option<u32>, event: Event) -> Self {
+        Self {
+            id,
+            seq,
+            provider: Arc::from(""),
+            received_at: Utc::now(),
+            event,
+            payload: None,
+        }
+    }
+    pub(crate) fn with_payload(mut self, payload: Arc<GeneralPayload>) -> Self {
+        self.payload = Some(payload);
+        self
+    }
+    /// 原始的 payload
+    pub fn payload(&self) -> Option<&GeneralPayload> {
+        self.payload.as_deref()
+    }
+    /// 用于 [`MessageSend::event_id`](crate::model::MessageSend::event_id) 的事件 id
+    pub fn event_id(&self) -> Option<&str> {
+        (!self.id.is_empty()).then_some(self.id.as_str())
+    }
+    pub fn into_event(self) -> Event {
+        self.event
+    }
+}
+
+impl Deref for EventEnvelope {
+    type Target = Event;
+
+    fn deref(&self) -> &Self::Target {
+        &self.event
+    }
+}
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+
+use super::Event;
+
+bitflags::bitflags! {
+    /// 订阅的事件，参考 https://bot.q.qq.com/wiki/develop/api-v2/dev-prepare/interface-framework/event-emit.html
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct Intents: u32 {
+        /// GUILD_CREATE, GUILD_UPDATE, GUILD_DELETE, CHANNEL_CREATE, CHANNEL_UPDATE, CHANNEL_DELETE
+        const GUILDS = 1 << 0;
+        /// GUILD_MEMBER_ADD, GUILD_MEMBER_UPDATE, GUILD_MEMBER_REMOVE
+        const GUILD_MEMBERS = 1 << 1;
+        /// MESSAGE_CREATE, MESSAGE_DELETE，仅私域机器人
+        const GUILD_MESSAGES = 1 << 9;
+        /// MESSAGE_REACTION_ADD, MESSAGE_REACTION_REMOVE
+        const GUILD_MESSAGE_REACTIONS = 1 << 10;
+        /// DIRECT_MESSAGE_CREATE, DIRECT_MESSAGE_DELETE
+        const DIRECT_MESSAGE = 1 << 12;
+        /// OPEN_FORUM_THREAD_*, OPEN_FORUM_POST_*, OPEN_FORUM_REPLY_*
+        const OPEN_FORUMS_EVENT = 1 << 18;
+        /// AUDIO_OR_LIVE_CHANNEL_MEMBER_ENTER, AUDIO_OR_LIVE_CHANNEL_MEMBER_EXIT
+        const AUDIO_OR_LIVE_CHANNEL_MEMBER = 1 << 19;
+        /// C2C_MESSAGE_CREATE, FRIEND_ADD, FRIEND_DEL, C2C_MSG_REJECT, C2C_MSG_RECEIVE,
+        /// GROUP_AT_MESSAGE_CREATE, GROUP_ADD_ROBOT, GROUP_DEL_ROBOT, GROUP_MSG_REJECT, GROUP_MSG_RECEIVE
+        const GROUP_AND_C2C_EVENT = 1 << 25;
+        /// INTERACTION_CREATE
+        const INTERACTION = 1 << 26;
+        /// MESSAGE_AUDIT_PASS, MESSAGE_AUDIT_REJECT
+        const MESSAGE_AUDIT = 1 << 27;
+        /// FORUM_THREAD_*, FORUM_POST_*, FORUM_REPLY_*, FORUM_PUBLISH_AUDIT_RESULT，仅私域机器人
+        const FORUMS_EVENT = 1 << 28;
+        /// AUDIO_START, AUDIO_FINISH, AUDIO_ON_MIC, AUDIO_OFF_MIC
+        const AUDIO_ACTION = 1 << 29;
+        /// AT_MESSAGE_CREATE, PUBLIC_MESSAGE_DELETE
+        const PUBLIC_GUILD_MESSAGES = 1 << 30;
+    }
+}
+
+impl Intents {
+    /// 事件类型（payload 中的 `t` 字段）所需要的 intent
+    pub fn from_event_type(event_type: &str) -> Option<Self> {
+        let intents = match event_type {
+            "GUILD_CREATE" | "GUILD_UPDATE" | "GUILD_DELETE" | "CHANNEL_CREATE"
+            | "CHANNEL_UPDATE" | "CHANNEL_DELETE" => Self::GUILDS,
+            "GUILD_MEMBER_ADD" | "GUILD_MEMBER_UPDATE" | "GUILD_MEMBER_REMOVE" => {
+                Self::GUILD_MEMBERS
+            }
+            "MESSAGE_CREATE" | "MESSAGE_DELETE" => Self::GUILD_MESSAGES,
+            "MESSAGE_REACTION_ADD" | "MESSAGE_REACTION_REMOVE" => Self::GUILD_MESSAGE_REACTIONS,
+            "DIRECT_MESSAGE_CREATE" | "DIRECT_MESSAGE_DELETE" => Self::DIRECT_MESSAGE,
+            "AUDIO_OR_LIVE_CHANNEL_MEMBER_ENTER" | "AUDIO_OR_LIVE_CHANNEL_MEMBER_EXIT" => {
+                Self::AUDIO_OR_LIVE_CHANNEL_MEMBER
+            }
+            "C2C_MESSAGE_CREATE"
+            | "FRIEND_ADD"
+            | "FRIEND_DEL"
+            | "C2C_MSG_REJECT"
+            | "C2C_MSG_RECEIVE"
+            | "GROUP_AT_MESSAGE_CREATE"
+            | "GROUP_ADD_ROBOT"
+            | "GROUP_DEL_ROBOT"
+            | "GROUP_MSG_REJECT"
+            | "GROUP_MSG_RECEIVE" => Self::GROUP_AND_C2C_EVENT,
+            "INTERACTION_CREATE" => Self::INTERACTION,
+            "MESSAGE_AUDIT_PASS" | "MESSAGE_AUDIT_REJECT" => Self::MESSAGE_AUDIT,
+            "FORUM_PUBLISH_AUDIT_RESULT" => Self::FORUMS_EVENT,
+            "AUDIO_START" | "AUDIO_FINISH" | "AUDIO_ON_MIC" | "AUDIO_OFF_MIC" => Self::AUDIO_ACTION,
+            "AT_MESSAGE_CREATE" | "PUBLIC_MESSAGE_DELETE" => Self::PUBLIC_GUILD_MESSAGES,
+            open_forum if open_forum.starts_with("OPEN_FORUM_") => Self::OPEN_FORUMS_EVENT,
+            forum if forum.starts_with("FORUM_") => Self::FORUMS_EVENT,
+            _ => return None,
+        };
+        Some(intents)
+    }
+
+    /// 订阅一组事件类型所需要的 intents，未知的事件类型会被忽略
+    pub fn for_event_types<'a>(event_types: impl IntoIterator<Item = &'a str>) -> Self {
+        event_types
+            .into_iter()
+            .filter_map(Self::from_event_type)
+            .fold(Self::empty(), |acc, intents| acc | intents)
+    }
+
+    /// 这些 intents 是否会收到这个事件
+    pub fn covers(&self, event: &Event) -> bool {
+        let required = event.intents();
+        !required.is_empty() && self.contains(required)
+    }
+}
+
+impl Serialize for Intents {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u32(self.bits())
+    }
+}
+
+impl<'de> Deserialize<'de> for Intents {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        u32::deserialize(deserializer).map(Self::from_bits_retain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_event_types() {
+        let intents = Intents::for_event_types([
+            "AT_MESSAGE_CREATE",
+            "GROUP_AT_MESSAGE_CREATE",
+            "C2C_MESSAGE_CREATE",
+            "FORUM_THREAD_CREATE",
+            "OPEN_FORUM_THREAD_CREATE",
+            "NOT_AN_EVENT",
+        ]);
+        assert_eq!(
+            intents,
+            Intents::PUBLIC_GUILD_MESSAGES
+                | Intents::GROUP_AND_C2C_EVENT
+                | Intents::FORUMS_EVENT
+                | Intents::OPEN_FORUMS_EVENT
+        );
+        assert_eq!(
+            intents.bits(),
+            (1 << 30) | (1 << 25) | (1 << 28) | (1 << 18)
+        );
+    }
+}
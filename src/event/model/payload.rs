@@ -1,9 +1,13 @@
 use serde::{Deserialize, Serialize};
 
-use super::{Event, Opcode};
+use std::sync::Arc;
+
+use super::{Event, EventEnvelope, Hello, Identify, Opcode, Ready, Resume, UnknownEvent};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct GeneralPayload {
+    /// websocket 下发的部分 payload 没有 id
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub(crate) id: String,
     #[serde(rename = "op")]
     pub(crate) opcode: Opcode,
@@ -39,20 +43,58 @@ pub struct HttpCallbackValidationResponse {
 
 pub enum InboundPayloadKind {
     HttpCallbackValidation(HttpCallbackValidationRequest),
-    Dispatch(Event),
+    Dispatch(EventEnvelope),
+    Hello(Hello),
+    Ready(Ready),
+    Resumed,
+    HeartbeatAck,
+    Reconnect,
+    InvalidSession,
 }
 
 impl GeneralPayload {
     pub fn into_inbound(self) -> crate::Result<InboundPayloadKind> {
         match self.opcode {
+            Opcode::Dispatch if self.event_type.as_deref() == Some("READY") => {
+                let data = self
+                    .data
+                    .ok_or(crate::Error::unexpected("ready data is missing"))?;
+                let ready: Ready = serde_json::from_value(data)
+                    .map_err(crate::Error::context("converting payload to ready"))?;
+                Ok(InboundPayloadKind::Ready(ready))
+            }
+            Opcode::Dispatch if self.event_type.as_deref() == Some("RESUMED") => {
+                Ok(InboundPayloadKind::Resumed)
+            }
             Opcode::Dispatch => {
-                let json_value = serde_json::json!({
-                    "kind": self.event_type,
-                    "data": self.data,
-                });
-                let event = serde_json::from_value::<Event>(json_value)
-                    .map_err(crate::Error::context("converting payload to event"))?;
-                Ok(InboundPayloadKind::Dispatch(event))
+                let kind = self
+                    .event_type
+                    .clone()
+                    .ok_or(crate::Error::unexpected("event type is missing"))?;
+                let payload = Arc::new(self);
+                let data = payload.data.as_ref().unwrap_or(&serde_json::Value::Null);
+                let event = if Event::is_known_kind(&kind) {
+                    let json_value = serde_json::json!({
+                        "kind": kind,
+                        "data": data,
+                    });
+                    match serde_json::from_value::<Event>(json_value) {
+                        Ok(event) => event,
+                        Err(err) => {
+                            tracing::warn!(kind, %err, "failed to parse event, fallback to unknown");
+                            let data = data.clone();
+                            Event::Unknown(Arc::new(UnknownEvent { kind, data }))
+                        }
+                    }
+                } else {
+                    tracing::debug!(kind, "unknown event");
+                    let data = data.clone();
+                    Event::Unknown(Arc::new(UnknownEvent { kind, data }))
+                };
+                Ok(InboundPayloadKind::Dispatch(
+                    EventEnvelope::new(payload.id.clone(), payload.seq, event)
+                        .with_payload(payload),
+                ))
             }
             Opcode::HttpCallbackValidation => {
                 let data = self.data.ok_or(crate::Error::unexpected(
@@ -63,6 +105,17 @@ impl GeneralPayload {
                 )?;
                 Ok(InboundPayloadKind::HttpCallbackValidation(request))
             }
+            Opcode::Hello => {
+                let data = self
+                    .data
+                    .ok_or(crate::Error::unexpected("hello data is missing"))?;
+                let hello: Hello = serde_json::from_value(data)
+                    .map_err(crate::Error::context("converting payload to hello"))?;
+                Ok(InboundPayloadKind::Hello(hello))
+            }
+            Opcode::HeartbeatAck => Ok(InboundPayloadKind::HeartbeatAck),
+            Opcode::Reconnect => Ok(InboundPayloadKind::Reconnect),
+            Opcode::InvalidSession => Ok(InboundPayloadKind::InvalidSession),
             _ => {
                 tracing::warn!("unexpected inbound opcode: {:?}", self.opcode);
                 Err(crate::Error::unexpected(format!(
@@ -82,4 +135,60 @@ impl GeneralPayload {
             data: None,
         }
     }
+
+    fn new_outbound(opcode: Opcode, data: serde_json::Value) -> Self {
+        Self {
+            id: String::new(),
+            opcode,
+            seq: None,
+            event_type: None,
+            data: Some(data),
+        }
+    }
+
+    pub fn new_heartbeat(last_seq: Option<u32>) -> Self {
+        Self::new_outbound(Opcode::Heartbeat, serde_json::json!(last_seq))
+    }
+
+    pub fn new_identify(identify: &Identify) -> crate::Result<Self> {
+        let data = serde_json::to_value(identify)
+            .map_err(crate::Error::context("serializing identify"))?;
+        Ok(Self::new_outbound(Opcode::Identify, data))
+    }
+
+    pub fn new_resume(resume: &Resume) -> crate::Result<Self> {
+        let data =
+            serde_json::to_value(resume).map_err(crate::Error::context("serializing resume"))?;
+        Ok(Self::new_outbound(Opcode::Resume, data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gateway_inbound() -> crate::Result<()> {
+        let hello: GeneralPayload =
+            serde_json::from_str(r#"{"op":10,"d":{"heartbeat_interval":45000}}"#)
+                .map_err(crate::Error::context("parse hello"))?;
+        assert!(matches!(
+            hello.into_inbound()?,
+            InboundPayloadKind::Hello(Hello {
+                heartbeat_interval: 45000
+            })
+        ));
+        let ready: GeneralPayload = serde_json::from_str(
+            r#"{"op":0,"s":1,"t":"READY","d":{"version":1,"session_id":"082ee18c-0be3-491b-9d8b-fbd95c51673a","user":{"id":"6158788878435714165","username":"群pro测试机器人","bot":true},"shard":[0,0]}}"#,
+        )
+        .map_err(crate::Error::context("parse ready"))?;
+        let InboundPayloadKind::Ready(ready) = ready.into_inbound()? else {
+            panic!("expect ready");
+        };
+        assert_eq!(ready.session_id, "082ee18c-0be3-491b-9d8b-fbd95c51673a");
+        let heartbeat = serde_json::to_string(&GeneralPayload::new_heartbeat(Some(42)))
+            .map_err(crate::Error::context("serialize heartbeat"))?;
+        assert_eq!(heartbeat, r#"{"op":1,"d":42}"#);
+        Ok(())
+    }
 }
@@ -0,0 +1,262 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Context, Poll},
+};
+
+use futures_util::task::AtomicWaker;
+use tokio::sync::Notify;
+
+/// 队列满时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// 等待队列有空位
+    #[default]
+    Block,
+    /// 丢弃最早的元素
+    DropOldest,
+    /// 拒绝新的元素
+    Reject,
+}
+
+/// 队列的计数器
+#[derive(Debug, Default)]
+pub struct QueueStats {
+    pushed: AtomicU64,
+    popped: AtomicU64,
+    dropped: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl QueueStats {
+    /// 入队的元素数量
+    pub fn pushed(&self) -> u64 {
+        self.pushed.load(Ordering::Relaxed)
+    }
+    /// 出队的元素数量
+    pub fn popped(&self) -> u64 {
+        self.popped.load(Ordering::Relaxed)
+    }
+    /// 因为 [`OverflowPolicy::DropOldest`] 被丢弃的元素数量
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+    /// 因为 [`OverflowPolicy::Reject`] 被拒绝的元素数量
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+    /// 尚未出队的元素数量
+    pub fn lag(&self) -> u64 {
+        self.pushed()
+            .saturating_sub(self.popped())
+            .saturating_sub(self.dropped())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushError<T> {
+    /// 队列已满，且策略为 [`OverflowPolicy::Reject`]
+    Full(T),
+    /// 队列已关闭
+    Closed(T),
+}
+
+impl<T> PushError<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            PushError::Full(item) | PushError::Closed(item) => item,
+        }
+    }
+}
+
+/// 有界的单消费者队列，满时按照 [`OverflowPolicy`] 处理
+#[derive(Debug)]
+pub struct BoundedQueue<T> {
+    inner: Arc<BoundedQueueInner<T>>,
+}
+
+impl<T> Clone for BoundedQueue<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct BoundedQueueInner<T> {
+    capacity: usize,
+    policy: OverflowPolicy,
+    state: Mutex<QueueState<T>>,
+    rx_waker: AtomicWaker,
+    not_full: Notify,
+    stats: Arc<QueueStats>,
+}
+
+#[derive(Debug)]
+struct QueueState<T> {
+    items: VecDeque<T>,
+    closed: bool,
+}
+
+impl<T> BoundedQueue<T> {
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            inner: Arc::new(BoundedQueueInner {
+                capacity,
+                policy,
+                state: Mutex::new(QueueState {
+                    items: VecDeque::with_capacity(capacity.min(1024)),
+                    closed: false,
+                }),
+                rx_waker: AtomicWaker::new(),
+                not_full: Notify::new(),
+                stats: Arc::new(QueueStats::default()),
+            }),
+        }
+    }
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity
+    }
+    pub fn policy(&self) -> OverflowPolicy {
+        self.inner.policy
+    }
+    pub fn stats(&self) -> Arc<QueueStats> {
+        self.inner.stats.clone()
+    }
+    pub fn len(&self) -> usize {
+        self.lock().items.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+    /// 关闭队列，之后的入队都会失败，已经在队列中的元素仍然可以出队
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.inner.rx_waker.wake();
+        self.inner.not_full.notify_waiters();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState<T>> {
+        self.inner.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 入队，队列满时按照 [`OverflowPolicy`] 处理
+    pub async fn push(&self, item: T) -> Result<(), PushError<T>> {
+        let mut item = item;
+        loop {
+            // enable before checking, so a pop or close between the check and the wait is not missed
+            let notified = self.inner.not_full.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            match self.try_push_inner(item) {
+                Ok(()) => return Ok(()),
+                Err(TryPush::Wait(returned)) => {
+                    item = returned;
+                    notified.await;
+                }
+                Err(TryPush::Fail(err)) => return Err(err),
+            }
+        }
+    }
+
+    /// 不等待的入队，[`OverflowPolicy::Block`] 下队列满时返回 [`PushError::Full`]
+    pub fn try_push(&self, item: T) -> Result<(), PushError<T>> {
+        match self.try_push_inner(item) {
+            Ok(()) => Ok(()),
+            Err(TryPush::Wait(item)) => Err(PushError::Full(item)),
+            Err(TryPush::Fail(err)) => Err(err),
+        }
+    }
+
+    fn try_push_inner(&self, item: T) -> Result<(), TryPush<T>> {
+        let mut state = self.lock();
+        if state.closed {
+            return Err(TryPush::Fail(PushError::Closed(item)));
+        }
+        if state.items.len() >= self.inner.capacity {
+            match self.inner.policy {
+                OverflowPolicy::Block => return Err(TryPush::Wait(item)),
+                OverflowPolicy::DropOldest => {
+                    state.items.pop_front();
+                    self.inner.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                OverflowPolicy::Reject => {
+                    self.inner.stats.rejected.fetch_add(1, Ordering::Relaxed);
+                    return Err(TryPush::Fail(PushError::Full(item)));
+                }
+            }
+        }
+        state.items.push_back(item);
+        self.inner.stats.pushed.fetch_add(1, Ordering::Relaxed);
+        drop(state);
+        self.inner.rx_waker.wake();
+        Ok(())
+    }
+
+    /// 出队，队列为空时等待，队列关闭且为空时返回 `None`
+    pub fn poll_pop(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut state = self.lock();
+        if let Some(item) = state.items.pop_front() {
+            drop(state);
+            self.inner.stats.popped.fetch_add(1, Ordering::Relaxed);
+            self.inner.not_full.notify_one();
+            return Poll::Ready(Some(item));
+        }
+        if state.closed {
+            return Poll::Ready(None);
+        }
+        // register while holding the lock so a concurrent push can't be missed
+        self.inner.rx_waker.register(cx.waker());
+        Poll::Pending
+    }
+
+    pub async fn pop(&self) -> Option<T> {
+        std::future::poll_fn(|cx| self.poll_pop(cx)).await
+    }
+}
+
+enum TryPush<T> {
+    Wait(T),
+    Fail(PushError<T>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_overflow_policy() {
+        let queue = BoundedQueue::new(2, OverflowPolicy::DropOldest);
+        for i in 0..3 {
+            assert!(queue.push(i).await.is_ok());
+        }
+        assert_eq!(queue.pop().await, Some(1));
+        assert_eq!(queue.stats().dropped(), 1);
+
+        let queue = BoundedQueue::new(1, OverflowPolicy::Reject);
+        assert!(queue.push(0).await.is_ok());
+        assert_eq!(queue.push(1).await, Err(PushError::Full(1)));
+        assert_eq!(queue.stats().rejected(), 1);
+
+        let queue = BoundedQueue::new(1, OverflowPolicy::Block);
+        assert!(queue.push(0).await.is_ok());
+        let pusher = {
+            let queue = queue.clone();
+            tokio::spawn(async move { queue.push(1).await })
+        };
+        assert_eq!(queue.pop().await, Some(0));
+        assert!(matches!(pusher.await, Ok(Ok(()))));
+        assert_eq!(queue.pop().await, Some(1));
+        queue.close();
+        assert_eq!(queue.pop().await, None);
+        assert_eq!(queue.push(2).await, Err(PushError::Closed(2)));
+    }
+}
@@ -0,0 +1,196 @@
+use std::sync::Arc;
+
+use futures_util::Stream;
+use tokio_util::sync::DropGuard;
+
+use super::{
+    handler::{Bot, EventHandler},
+    model::{Event, EventEnvelope, Intents},
+    queue::{BoundedQueue, OverflowPolicy, PushError, QueueStats},
+};
+
+/// 某一种事件，用于 [`EventService::subscribe`](crate::event::EventService::subscribe)
+pub trait EventKind: Send + Sync + 'static {
+    /// 事件类型，如 `AT_MESSAGE_CREATE`
+    const KIND: &'static str;
+    type Data: Send + Sync + 'static;
+    fn extract(event: &Event) -> Option<&Arc<Self::Data>>;
+}
+
+macro_rules! event_kinds {
+    ($($variant:ident($kind:literal) => $data:ty),* $(,)?) => {
+        /// 每种事件对应的标记类型
+        pub mod kind {
+            use std::sync::Arc;
+
+            use crate::{event::model::*, model::*};
+
+            /// SDK 支持的所有事件类型
+            pub const ALL: &[&str] = Event::KINDS;
+
+            $(
+                #[doc = concat!("`", $kind, "`")]
+                #[derive(Debug, Clone, Copy)]
+                pub struct $variant;
+
+                impl super::EventKind for $variant {
+                    const KIND: &'static str = $kind;
+                    type Data = $data;
+                    fn extract(event: &Event) -> Option<&Arc<Self::Data>> {
+                        match event {
+                            Event::$variant(data) => Some(data),
+                            _ => None,
+                        }
+                    }
+                }
+            )*
+        }
+    };
+}
+
+crate::event::model::with_event_kinds!(event_kinds);
+
+/// 订阅队列的选项，用于 [`EventService::subscribe_with_options`](crate::event::EventService::subscribe_with_options)
+#[derive(Debug, Clone)]
+pub struct SubscribeOptions {
+    /// 订阅队列的容量
+    pub queue_size: usize,
+    /// 订阅者来不及读取时的处理方式，默认为 [`OverflowPolicy::DropOldest`]，丢弃时输出警告
+    ///
+    /// [`OverflowPolicy::Block`] 时转发会等待订阅者，等待期间的事件在转发处理器的队列中按照它的策略处理
+    pub overflow_policy: OverflowPolicy,
+}
+
+impl SubscribeOptions {
+    pub const DEFAULT_QUEUE_SIZE: usize = 1024;
+    pub fn with_queue_size(mut self, queue_size: usize) -> Self {
+        self.queue_size = queue_size;
+        self
+    }
+    pub fn with_overflow_policy(mut self, overflow_policy: OverflowPolicy) -> Self {
+        self.overflow_policy = overflow_policy;
+        self
+    }
+}
+
+impl Default for SubscribeOptions {
+    fn default() -> Self {
+        Self {
+            queue_size: Self::DEFAULT_QUEUE_SIZE,
+            overflow_policy: OverflowPolicy::DropOldest,
+        }
+    }
+}
+
+/// 把某一种事件转发到 [`Subscription`] 的处理器
+pub(crate) struct Forwarder<K: EventKind> {
+    pub(crate) queue: BoundedQueue<Arc<K::Data>>,
+}
+
+impl<K: EventKind> Forwarder<K> {
+    fn forward(&self, data: Arc<K::Data>) {
+        let stats = self.queue.stats();
+        let dropped = stats.dropped();
+        match self.queue.try_push(data) {
+            Ok(()) if stats.dropped() > dropped => {
+                tracing::warn!(
+                    kind = K::KIND,
+                    dropped = stats.dropped(),
+                    "subscriber is lagging, dropped the oldest event"
+                );
+            }
+            Ok(()) => {}
+            Err(PushError::Full(_)) => {
+                tracing::warn!(
+                    kind = K::KIND,
+                    skipped = stats.rejected(),
+                    "subscriber is lagging, skipped event"
+                );
+            }
+            // the subscription is dropped, the handler is shutting down
+            Err(PushError::Closed(_)) => {}
+        }
+    }
+}
+
+impl<C, K> EventHandler<C> for Forwarder<K>
+where
+    C: Clone + Send + 'static,
+    K: EventKind,
+{
+    fn intents(&self) -> Intents {
+        Intents::from_event_type(K::KIND).unwrap_or_default()
+    }
+    fn would_handle(&self, event: &EventEnvelope, _bot: &Bot<C>) -> bool {
+        K::extract(&event.event).is_some()
+    }
+    fn handle(
+        &self,
+        event: EventEnvelope,
+        _bot: &Bot<C>,
+    ) -> impl Future<Output = crate::Result<()>> + Send {
+        // don't hold the bot across the await, `C` is not required to be `Sync`
+        let mut blocked = None;
+        if let Some(data) = K::extract(&event.event).cloned() {
+            if self.queue.policy() == OverflowPolicy::Block {
+                blocked = Some((self.queue.clone(), data));
+            } else {
+                self.forward(data);
+            }
+        }
+        async move {
+            if let Some((queue, data)) = blocked {
+                // a closed queue means the subscription is dropped, the handler is shutting down
+                let _ = queue.push(data).await;
+            }
+            Ok(())
+        }
+    }
+}
+
+impl<K: EventKind> Drop for Forwarder<K> {
+    fn drop(&mut self) {
+        // the handler stopped, let the subscriber see the end
+        self.queue.close();
+    }
+}
+
+/// 某一种事件的订阅，drop 之后自动注销
+#[derive(Debug)]
+pub struct Subscription<T> {
+    queue: BoundedQueue<Arc<T>>,
+    _guard: DropGuard,
+}
+
+impl<T> Subscription<T> {
+    pub(crate) fn new(queue: BoundedQueue<Arc<T>>, guard: DropGuard) -> Self {
+        Self {
+            queue,
+            _guard: guard,
+        }
+    }
+    /// 等待下一个事件，处理器停止之后返回 `None`
+    pub async fn recv(&mut self) -> Option<Arc<T>> {
+        self.queue.pop().await
+    }
+    /// 订阅队列的计数器，可以用来观察丢弃的事件
+    pub fn stats(&self) -> Arc<QueueStats> {
+        self.queue.stats()
+    }
+}
+
+impl<T> Stream for Subscription<T> {
+    type Item = Arc<T>;
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.queue.poll_pop(cx)
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        self.queue.close();
+    }
+}
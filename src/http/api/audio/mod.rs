@@ -0,0 +1,66 @@
+use serde::Serialize;
+
+use crate::model::{AudioControl, ChannelId};
+
+use super::{Api, Empty};
+
+/// 音频控制
+pub struct PostAudio<'a> {
+    marker: std::marker::PhantomData<&'a ()>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PostAudioRequest<'a> {
+    #[serde(skip)]
+    pub channel_id: ChannelId,
+    #[serde(flatten)]
+    pub control: &'a AudioControl,
+}
+
+impl<'a> Api for PostAudio<'a> {
+    type Request = PostAudioRequest<'a>;
+
+    type Response = Empty;
+
+    const METHOD: http::Method = http::Method::POST;
+
+    fn path(request: &Self::Request) -> impl std::fmt::Display {
+        format!("/channels/{}/audio", request.channel_id)
+    }
+}
+
+/// 机器人在语音子频道上麦
+pub struct PutMic;
+
+/// 机器人在语音子频道下麦
+pub struct DeleteMic;
+
+#[derive(Debug, Serialize)]
+pub struct MicRequest {
+    #[serde(skip)]
+    pub channel_id: ChannelId,
+}
+
+impl Api for PutMic {
+    type Request = MicRequest;
+
+    type Response = Empty;
+
+    const METHOD: http::Method = http::Method::PUT;
+
+    fn path(request: &Self::Request) -> impl std::fmt::Display {
+        format!("/channels/{}/mic", request.channel_id)
+    }
+}
+
+impl Api for DeleteMic {
+    type Request = MicRequest;
+
+    type Response = Empty;
+
+    const METHOD: http::Method = http::Method::DELETE;
+
+    fn path(request: &Self::Request) -> impl std::fmt::Display {
+        format!("/channels/{}/mic", request.channel_id)
+    }
+}
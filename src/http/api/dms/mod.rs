@@ -0,0 +1,98 @@
+use serde::Serialize;
+use serde_with::{DisplayFromStr, serde_as};
+
+use crate::model::{Dms, GuildId, MessageBotRecieved, MessageId, MessageSend};
+
+use super::{Api, Empty, message::PostMessageRequest};
+
+/// 创建私信会话
+pub struct PostDms<'a> {
+    marker: std::marker::PhantomData<&'a ()>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+pub struct PostDmsRequest<'a> {
+    /// 接收者 id
+    pub recipient_id: &'a str,
+    #[serde_as(as = "DisplayFromStr")]
+    /// 源频道 id，机器人和接收者需要在同一个频道中
+    pub source_guild_id: GuildId,
+}
+
+impl<'a> Api for PostDms<'a> {
+    type Request = PostDmsRequest<'a>;
+
+    type Response = Dms;
+
+    const METHOD: http::Method = http::Method::POST;
+
+    const PATH: &'static str = "/users/@me/dms";
+}
+
+/// 发送私信
+pub struct PostDmsMessage<'a> {
+    marker: std::marker::PhantomData<&'a ()>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PostDmsMessageRequest<'a> {
+    #[serde(skip)]
+    /// 私信会话的 guild_id，见 [`Dms`]
+    pub guild_id: GuildId,
+    #[serde(flatten)]
+    pub body: PostMessageRequest<'a>,
+}
+
+impl<'a> PostDmsMessageRequest<'a> {
+    pub fn new(guild_id: GuildId, message: &'a MessageSend) -> Self {
+        Self {
+            guild_id,
+            // 私信不使用 channel_id，它不会被序列化
+            body: PostMessageRequest::new(0, message),
+        }
+    }
+}
+
+impl<'a> Api for PostDmsMessage<'a> {
+    type Request = PostDmsMessageRequest<'a>;
+
+    type Response = MessageBotRecieved;
+
+    const METHOD: http::Method = http::Method::POST;
+
+    fn path(request: &Self::Request) -> impl std::fmt::Display {
+        format!("/dms/{}/messages", request.guild_id)
+    }
+}
+
+/// 撤回私信
+pub struct DeleteDmsMessage;
+
+#[derive(Debug, Serialize)]
+pub struct DeleteDmsMessageRequest {
+    #[serde(skip)]
+    /// 私信会话的 guild_id
+    pub guild_id: GuildId,
+    #[serde(skip)]
+    /// 消息 id
+    pub message_id: MessageId,
+    #[serde(skip)]
+    /// 是否隐藏提示小灰条，true 为隐藏，false 为显示
+    pub hidetip: bool,
+}
+
+impl Api for DeleteDmsMessage {
+    type Request = DeleteDmsMessageRequest;
+
+    type Response = Empty;
+
+    const METHOD: http::Method = http::Method::DELETE;
+
+    fn path(request: &Self::Request) -> impl std::fmt::Display {
+        format!(
+            "/dms/{}/messages/{}?hidetip={}",
+            request.guild_id, request.message_id, request.hidetip
+        )
+    }
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::{ChannelId, Thread, ThreadFormat, ThreadPublished};
+
+use super::{Api, Empty};
+
+/// 获取子频道的帖子列表
+pub struct GetThreads;
+
+#[derive(Debug, Serialize)]
+pub struct GetThreadsRequest {
+    #[serde(skip)]
+    pub channel_id: ChannelId,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct GetThreadsResponse {
+    #[serde(default)]
+    /// 帖子列表
+    pub threads: Vec<Thread>,
+    #[serde(default)]
+    /// 是否拉取完毕，0 否，1 是
+    pub is_finish: u32,
+}
+
+impl Api for GetThreads {
+    type Request = GetThreadsRequest;
+
+    type Response = GetThreadsResponse;
+
+    const METHOD: http::Method = http::Method::GET;
+
+    fn path(request: &Self::Request) -> impl std::fmt::Display {
+        format!("/channels/{}/threads", request.channel_id)
+    }
+}
+
+/// 获取帖子详情
+pub struct GetThread<'a> {
+    marker: std::marker::PhantomData<&'a ()>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GetThreadRequest<'a> {
+    #[serde(skip)]
+    pub channel_id: ChannelId,
+    #[serde(skip)]
+    pub thread_id: &'a str,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct GetThreadResponse {
+    /// 帖子详情
+    pub thread: Thread,
+}
+
+impl<'a> Api for GetThread<'a> {
+    type Request = GetThreadRequest<'a>;
+
+    type Response = GetThreadResponse;
+
+    const METHOD: http::Method = http::Method::GET;
+
+    fn path(request: &Self::Request) -> impl std::fmt::Display {
+        format!(
+            "/channels/{}/threads/{}",
+            request.channel_id, request.thread_id
+        )
+    }
+}
+
+/// 发表帖子
+pub struct PutThread<'a> {
+    marker: std::marker::PhantomData<&'a ()>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PutThreadRequest<'a> {
+    #[serde(skip)]
+    pub channel_id: ChannelId,
+    /// 帖子标题
+    pub title: &'a str,
+    /// 帖子内容
+    pub content: &'a str,
+    /// 帖子内容的格式
+    pub format: ThreadFormat,
+}
+
+impl<'a> Api for PutThread<'a> {
+    type Request = PutThreadRequest<'a>;
+
+    type Response = ThreadPublished;
+
+    const METHOD: http::Method = http::Method::PUT;
+
+    fn path(request: &Self::Request) -> impl std::fmt::Display {
+        format!("/channels/{}/threads", request.channel_id)
+    }
+}
+
+/// 删除帖子
+pub struct DeleteThread<'a> {
+    marker: std::marker::PhantomData<&'a ()>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteThreadRequest<'a> {
+    #[serde(skip)]
+    pub channel_id: ChannelId,
+    #[serde(skip)]
+    pub thread_id: &'a str,
+}
+
+impl<'a> Api for DeleteThread<'a> {
+    type Request = DeleteThreadRequest<'a>;
+
+    type Response = Empty;
+
+    const METHOD: http::Method = http::Method::DELETE;
+
+    fn path(request: &Self::Request) -> impl std::fmt::Display {
+        format!(
+            "/channels/{}/threads/{}",
+            request.channel_id, request.thread_id
+        )
+    }
+}
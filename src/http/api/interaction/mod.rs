@@ -0,0 +1,30 @@
+use serde::Serialize;
+
+use crate::model::InteractionResult;
+
+use super::{Api, Empty};
+
+/// 回应互动事件
+pub struct PutInteraction<'a> {
+    marker: std::marker::PhantomData<&'a ()>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PutInteractionRequest<'a> {
+    #[serde(skip)]
+    pub interaction_id: &'a str,
+    /// 互动结果
+    pub code: InteractionResult,
+}
+
+impl<'a> Api for PutInteraction<'a> {
+    type Request = PutInteractionRequest<'a>;
+
+    type Response = Empty;
+
+    const METHOD: http::Method = http::Method::PUT;
+
+    fn path(request: &Self::Request) -> impl std::fmt::Display {
+        format!("/interactions/{}", request.interaction_id)
+    }
+}
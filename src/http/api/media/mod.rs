@@ -0,0 +1,94 @@
+use base64::Engine;
+use serde::Serialize;
+
+use crate::model::{FileType, Media};
+
+use super::Api;
+
+/// 富媒体上传的请求体
+#[derive(Serialize, Debug)]
+pub struct UploadMediaBody<'a> {
+    /// 媒体类型
+    pub file_type: FileType,
+    /// 需要发送媒体资源的url
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<&'a str>,
+    /// base64 编码的文件内容，与 url 二选一
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_data: Option<String>,
+    /// 设置 true 会直接发送消息到目标端，且会占用主动消息频次
+    pub srv_send_msg: bool,
+}
+
+impl<'a> UploadMediaBody<'a> {
+    pub fn from_url(file_type: FileType, url: &'a str) -> Self {
+        Self {
+            file_type,
+            url: Some(url),
+            file_data: None,
+            srv_send_msg: false,
+        }
+    }
+    pub fn from_bytes(file_type: FileType, data: impl AsRef<[u8]>) -> Self {
+        Self {
+            file_type,
+            url: None,
+            file_data: Some(base64::engine::general_purpose::STANDARD.encode(data)),
+            srv_send_msg: false,
+        }
+    }
+    pub fn srv_send_msg(mut self, srv_send_msg: bool) -> Self {
+        self.srv_send_msg = srv_send_msg;
+        self
+    }
+}
+
+/// 上传群聊富媒体文件
+pub struct PostGroupFile<'a> {
+    marker: std::marker::PhantomData<&'a ()>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PostGroupFileRequest<'a> {
+    #[serde(skip)]
+    pub group_openid: &'a str,
+    #[serde(flatten)]
+    pub body: &'a UploadMediaBody<'a>,
+}
+
+impl<'a> Api for PostGroupFile<'a> {
+    type Request = PostGroupFileRequest<'a>;
+
+    type Response = Media;
+
+    const METHOD: http::Method = http::Method::POST;
+
+    fn path(request: &Self::Request) -> impl std::fmt::Display {
+        format!("/v2/groups/{}/files", request.group_openid)
+    }
+}
+
+/// 上传单聊富媒体文件
+pub struct PostC2cFile<'a> {
+    marker: std::marker::PhantomData<&'a ()>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PostC2cFileRequest<'a> {
+    #[serde(skip)]
+    pub openid: &'a str,
+    #[serde(flatten)]
+    pub body: &'a UploadMediaBody<'a>,
+}
+
+impl<'a> Api for PostC2cFile<'a> {
+    type Request = PostC2cFileRequest<'a>;
+
+    type Response = Media;
+
+    const METHOD: http::Method = http::Method::POST;
+
+    fn path(request: &Self::Request) -> impl std::fmt::Display {
+        format!("/v2/users/{}/files", request.openid)
+    }
+}
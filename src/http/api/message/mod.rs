@@ -1,9 +1,9 @@
 use crate::model::{
     MessageArk, MessageBotRecieved, MessageDescriptor, MessageEmbed, MessageId, MessageMarkdown,
-    MessageReference, MessageSend,
+    MessageMedia, MessageReference, MessageSend, MessageSent, MessageType,
 };
 
-use super::Api;
+use super::{Api, Empty};
 use serde::Serialize;
 
 pub struct GetMessage;
@@ -85,6 +85,107 @@ impl<'a> Api for PostMessage<'a> {
     }
 }
 
+/// 群聊和单聊（/v2）的消息体
+#[derive(Serialize, Default, Debug)]
+pub struct PostMessageV2Body<'a> {
+    /// 选填，文本内容
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<&'a str>,
+    /// 必填，消息类型
+    pub msg_type: MessageType,
+    /// 选填，markdown 消息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub markdown: Option<&'a MessageMarkdown>,
+    /// 选填，ark 消息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ark: Option<&'a MessageArk>,
+    /// 选填，embed 消息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embed: Option<&'a MessageEmbed>,
+    /// 选填，富媒体消息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media: Option<&'a MessageMedia>,
+    /// 选填，引用消息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_reference: Option<&'a MessageReference>,
+    /// 选填，前置收到的事件 id，用于发送被动消息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<&'a str>,
+    /// 选填，前置收到的用户发送过来的消息 id，用于发送被动消息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub msg_id: Option<MessageId>,
+    /// 选填，回复消息的序号，与 msg_id 联合使用，相同的 msg_id + msg_seq 重复发送会失败
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub msg_seq: Option<u32>,
+}
+
+impl<'a> PostMessageV2Body<'a> {
+    pub fn new(message: &'a MessageSend, msg_seq: Option<u32>) -> Self {
+        Self {
+            content: message.content,
+            msg_type: message.msg_type(),
+            markdown: message.markdown.as_ref(),
+            ark: message.ark.as_ref(),
+            embed: message.embed.as_ref(),
+            media: message.media.as_ref(),
+            message_reference: message.message_reference.as_ref(),
+            event_id: message.event_id,
+            msg_id: message.msg_id.clone(),
+            msg_seq,
+        }
+    }
+}
+
+/// 发送群聊消息
+pub struct PostGroupMessage<'a> {
+    marker: std::marker::PhantomData<&'a ()>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PostGroupMessageRequest<'a> {
+    #[serde(skip)]
+    pub group_openid: &'a str,
+    #[serde(flatten)]
+    pub body: PostMessageV2Body<'a>,
+}
+
+impl<'a> Api for PostGroupMessage<'a> {
+    type Request = PostGroupMessageRequest<'a>;
+
+    type Response = MessageSent;
+
+    const METHOD: http::Method = http::Method::POST;
+
+    fn path(request: &Self::Request) -> impl std::fmt::Display {
+        format!("/v2/groups/{}/messages", request.group_openid)
+    }
+}
+
+/// 发送单聊消息
+pub struct PostC2cMessage<'a> {
+    marker: std::marker::PhantomData<&'a ()>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PostC2cMessageRequest<'a> {
+    #[serde(skip)]
+    pub openid: &'a str,
+    #[serde(flatten)]
+    pub body: PostMessageV2Body<'a>,
+}
+
+impl<'a> Api for PostC2cMessage<'a> {
+    type Request = PostC2cMessageRequest<'a>;
+
+    type Response = MessageSent;
+
+    const METHOD: http::Method = http::Method::POST;
+
+    fn path(request: &Self::Request) -> impl std::fmt::Display {
+        format!("/v2/users/{}/messages", request.openid)
+    }
+}
+
 /// 撤回消息
 pub struct DeleteMessage;
 
@@ -99,7 +200,7 @@ pub struct DeleteMessageRequest {
 impl Api for DeleteMessage {
     type Request = MessageDescriptor;
 
-    type Response = ();
+    type Response = Empty;
 
     const METHOD: http::Method = http::Method::DELETE;
 
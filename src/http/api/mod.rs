@@ -1,5 +1,10 @@
 pub mod app;
+pub mod audio;
+pub mod dms;
+pub mod forum;
 pub mod guild;
+pub mod interaction;
+pub mod media;
 pub mod message;
 pub mod reaction;
 pub mod user;
@@ -80,3 +85,39 @@ impl<T> Response<T> {
         }
     }
 }
+
+/// 没有返回内容的接口统一使用的响应类型，成功时可能是空对象 `{}` 也可能是空的响应体
+///
+/// 不能直接用 `()`，它只能从 `null` 反序列化；也不能用一个空结构体，它会把失败的响应也当作成功
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Empty;
+
+impl<'de> Deserialize<'de> for Empty {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match Option::<serde_json::Map<String, serde_json::Value>>::deserialize(deserializer)? {
+            Some(map) if !map.is_empty() => Err(serde::de::Error::custom("expect an empty object")),
+            _ => Ok(Empty),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_response() {
+        let resp = serde_json::from_str::<Response<Empty>>("{}").expect("valid json");
+        assert!(resp.as_result().is_ok());
+        let resp = serde_json::from_str::<Response<Empty>>("null").expect("valid json");
+        assert!(resp.as_result().is_ok());
+        let resp = serde_json::from_str::<Response<Empty>>(
+            r#"{"code":304003,"message":"url not allowed"}"#,
+        )
+        .expect("valid json");
+        assert_eq!(resp.as_result().expect_err("should fail").code, 304003);
+    }
+}
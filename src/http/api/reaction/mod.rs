@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::model::{Emoji, MessageId, User};
 
-use super::Api;
+use super::{Api, Empty};
 
 /// 发表表情表态
 pub struct SendEmojiReaction;
@@ -31,7 +31,7 @@ pub struct EmojiReactionDescriptor {
 impl Api for SendEmojiReaction {
     type Request = EmojiReactionDescriptor;
 
-    type Response = ();
+    type Response = Empty;
 
     const METHOD: http::Method = http::Method::PUT;
 
@@ -85,7 +85,7 @@ pub struct GetEmojiReactionUserListResponse {
 impl Api for DeleteEmojiReaction {
     type Request = EmojiReactionDescriptor;
 
-    type Response = ();
+    type Response = Empty;
 
     const METHOD: http::Method = http::Method::DELETE;
 
@@ -85,6 +85,23 @@ impl ApiClient {
         *auth_header = Some((new_header.clone(), expire_at));
         Ok(new_header)
     }
+    /// 获取当前的授权头，即将过期时在后台刷新，已经过期时阻塞刷新
+    pub async fn auth_header(&self) -> crate::Result<HeaderValue> {
+        let cached = self.auth_header.read().await.clone();
+        match cached {
+            Some((header_value, expire_at)) if expire_at >= Instant::now() => {
+                if expire_at < Instant::now() + std::time::Duration::from_secs(60) {
+                    let client = self.clone();
+                    tokio::spawn(async move {
+                        let _ = client.refresh_auth_header().await;
+                    });
+                }
+                Ok(header_value)
+            }
+            // blocking get new token
+            _ => self.refresh_auth_header().await,
+        }
+    }
     /// 发送一个请求
     ///
     /// 例子
@@ -92,28 +109,7 @@ impl ApiClient {
     /// let resp = client.send::<Getway>::(&()).await?
     /// ```
     pub async fn send<A: Api>(&self, request: &A::Request) -> crate::Result<Response<A::Response>> {
-        // check if the token is expired
-
-        let auth_header = {
-            let auth_header = self.auth_header.read().await;
-            if let Some((header_value, expire_at)) = auth_header.as_ref() {
-                if *expire_at < Instant::now() {
-                    // blocking get new token
-                    self.refresh_auth_header().await?
-                } else if *expire_at < Instant::now() + std::time::Duration::from_secs(60) {
-                    let client = self.clone();
-                    tokio::spawn(async move {
-                        let _ = client.refresh_auth_header().await;
-                    });
-                    header_value.clone()
-                } else {
-                    header_value.clone()
-                }
-            } else {
-                // blocking get new token
-                self.refresh_auth_header().await?
-            }
-        };
+        let auth_header = self.auth_header().await?;
         let url = Url::parse(format!("{}{}", self.base_url, A::path(request)).as_str())
             .expect("invalid url, report this bug");
         let resp = self
@@ -124,8 +120,13 @@ impl ApiClient {
             .send()
             .await
             .map_err(crate::Error::context("send request"))?;
-        resp.json::<Response<A::Response>>()
+        let body = resp
+            .bytes()
             .await
+            .map_err(crate::Error::context("read response"))?;
+        // some apis (PUT/DELETE) respond with an empty body on success
+        let body: &[u8] = if body.is_empty() { b"null" } else { &body };
+        serde_json::from_slice::<Response<A::Response>>(body)
             .map_err(crate::Error::context("parse response"))
     }
 }
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use serde_with::{DisplayFromStr, serde_as};
+
+use super::{ChannelId, GuildId};
+
+/// 音频事件
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AudioAction {
+    #[serde_as(as = "DisplayFromStr")]
+    /// 频道 id
+    pub guild_id: GuildId,
+    #[serde_as(as = "DisplayFromStr")]
+    /// 子频道 id
+    pub channel_id: ChannelId,
+    #[serde(default)]
+    /// 音频数据的 url，仅 AUDIO_START 事件有值
+    pub audio_url: Option<String>,
+    #[serde(default)]
+    /// 状态文本（比如：简单爱-周杰伦），仅 AUDIO_START 事件有值
+    pub text: Option<String>,
+}
+
+/// 用户进出音视频或直播子频道
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AudioLiveChannelMember {
+    #[serde_as(as = "DisplayFromStr")]
+    /// 频道 id
+    pub guild_id: GuildId,
+    #[serde_as(as = "DisplayFromStr")]
+    /// 子频道 id
+    pub channel_id: ChannelId,
+    /// 2 音视频子频道，5 直播子频道
+    pub channel_type: u32,
+    /// 用户 id
+    pub user_id: String,
+}
+
+/// 音频控制
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AudioControl {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// 音频数据的 url，status 为 [`AudioStatus::Start`] 时有效
+    pub audio_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// 状态文本（比如：简单爱-周杰伦），status 为 [`AudioStatus::Start`] 时有效
+    pub text: Option<String>,
+    /// 播放状态
+    pub status: AudioStatus,
+}
+
+impl AudioControl {
+    /// 开始播放
+    pub fn play(audio_url: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            audio_url: Some(audio_url.into()),
+            text: Some(text.into()),
+            status: AudioStatus::Start,
+        }
+    }
+    /// 暂停播放
+    pub fn pause() -> Self {
+        Self::with_status(AudioStatus::Pause)
+    }
+    /// 继续播放
+    pub fn resume() -> Self {
+        Self::with_status(AudioStatus::Resume)
+    }
+    /// 停止播放
+    pub fn stop() -> Self {
+        Self::with_status(AudioStatus::Stop)
+    }
+    fn with_status(status: AudioStatus) -> Self {
+        Self {
+            audio_url: None,
+            text: None,
+            status,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize_repr, Deserialize_repr)]
+#[repr(u8)]
+pub enum AudioStatus {
+    /// 开始播放
+    Start = 0,
+    /// 暂停播放
+    Pause = 1,
+    /// 继续播放
+    Resume = 2,
+    /// 停止播放
+    Stop = 3,
+}
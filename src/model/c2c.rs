@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::{TimestampSeconds, formats::Flexible, serde_as};
+
+use super::{MessageAttachment, MessageId};
+
+/// 单聊用户，单聊场景下使用 openid 标识用户
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct C2cUser {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// 用户在机器人下的 user_openid
+    pub user_openid: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub union_openid: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct C2cMessageRecieved {
+    /// 平台方消息 id，可以用于被动消息发送
+    pub id: MessageId,
+    #[serde(default)]
+    /// 消息内容
+    pub content: String,
+    /// 消息生产时间
+    pub timestamp: DateTime<Utc>,
+    /// 发送者
+    pub author: C2cUser,
+    #[serde(default)]
+    /// 富媒体文件附件
+    pub attachments: Vec<MessageAttachment>,
+}
+
+/// 用户添加/删除机器人，用户打开/关闭主动消息推送
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FriendOperation {
+    /// 用户的 openid
+    pub openid: String,
+    #[serde_as(as = "TimestampSeconds<i64, Flexible>")]
+    /// 操作时间
+    pub timestamp: DateTime<Utc>,
+}
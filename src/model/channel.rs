@@ -20,24 +20,39 @@ pub struct Channel {
     pub r#type: ChannelType,
     /// 子频道子类型 ChannelSubType
     pub sub_type: ChannelSubType,
+    #[serde(default)]
     /// 排序值，具体请参考 有关 position 的说明
     pub position: i32,
+    #[serde(default)]
     /// 所属分组 id，仅对子频道有效，对 子频道分组（ChannelType=4） 无效
     pub parent_id: String,
     #[serde_as(as = "DisplayFromStr")]
     /// 创建人 id
     pub owner_id: u64,
+    #[serde(default)]
     /// 子频道私密类型 PrivateType
     pub private_type: PrivateType,
+    #[serde(default)]
     /// 子频道发言权限 SpeakPermission
     pub speak_permission: SpeakPermission,
     #[serde(skip_serializing_if = "Option::is_none")]
     /// 用于标识应用子频道应用类型，仅应用子频道时会使用该字段，具体定义请参考 应用子频道的应用类型
     pub application_id: Option<String>,
+    #[serde(default)]
     /// 用户拥有的子频道权限 Permissions
     pub permissions: String,
 }
 
+/// 子频道事件推送的子频道对象，只包含子频道的基础信息
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChannelWithOpUser {
+    #[serde(flatten)]
+    pub channel: Channel,
+    #[serde(default)]
+    /// 操作人 id
+    pub op_user_id: Option<String>,
+}
+
 #[derive(Serialize_repr, Deserialize_repr, Clone, Debug)]
 #[non_exhaustive]
 #[repr(i32)]
@@ -76,10 +91,11 @@ pub enum ChannelSubType {
     Unsupported = i32::MAX,
 }
 
-#[derive(Serialize_repr, Deserialize_repr, Clone, Debug)]
+#[derive(Serialize_repr, Deserialize_repr, Clone, Debug, Default)]
 #[repr(i32)]
 pub enum PrivateType {
     /// 公开频道
+    #[default]
     Public = 0,
     /// 群主管理员可见
     OnlyAdmin = 1,
@@ -87,10 +103,11 @@ pub enum PrivateType {
     CertainMembers = 2,
 }
 
-#[derive(Serialize_repr, Deserialize_repr, Clone, Debug)]
+#[derive(Serialize_repr, Deserialize_repr, Clone, Debug, Default)]
 #[repr(i32)]
 pub enum SpeakPermission {
     /// 无效类型
+    #[default]
     Invalid = 0,
     /// 所有人
     All = 1,
@@ -0,0 +1,20 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, TimestampSeconds, formats::Flexible, serde_as};
+
+use super::{ChannelId, GuildId};
+
+/// 私信会话
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Dms {
+    #[serde_as(as = "DisplayFromStr")]
+    /// 私信会话关联的频道 id，发送私信时使用
+    pub guild_id: GuildId,
+    #[serde_as(as = "DisplayFromStr")]
+    /// 私信会话关联的子频道 id
+    pub channel_id: ChannelId,
+    #[serde_as(as = "TimestampSeconds<i64, Flexible>")]
+    /// 创建私信会话时间戳
+    pub create_time: DateTime<Utc>,
+}
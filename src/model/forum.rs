@@ -0,0 +1,335 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use serde_with::{DisplayFromStr, TimestampSeconds, formats::Flexible, serde_as};
+
+use super::{ChannelId, GuildId};
+
+/// 论坛主题（帖子）
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Thread {
+    #[serde_as(as = "DisplayFromStr")]
+    /// 频道 id
+    pub guild_id: GuildId,
+    #[serde_as(as = "DisplayFromStr")]
+    /// 子频道 id
+    pub channel_id: ChannelId,
+    /// 作者 id
+    pub author_id: String,
+    /// 主题内容
+    pub thread_info: ThreadInfo,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ThreadInfo {
+    /// 主题 id
+    pub thread_id: String,
+    /// 标题
+    pub title: String,
+    /// 内容，事件中是 [`RichText`] 序列化后的 json 字符串
+    pub content: String,
+    /// 发表时间
+    pub date_time: DateTime<Utc>,
+}
+
+impl ThreadInfo {
+    /// 按照 [`RichText`] 解析内容
+    pub fn rich_content(&self) -> crate::Result<RichText> {
+        RichText::from_json(&self.content)
+    }
+}
+
+/// 论坛评论
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Post {
+    #[serde_as(as = "DisplayFromStr")]
+    /// 频道 id
+    pub guild_id: GuildId,
+    #[serde_as(as = "DisplayFromStr")]
+    /// 子频道 id
+    pub channel_id: ChannelId,
+    /// 作者 id
+    pub author_id: String,
+    /// 评论内容
+    pub post_info: PostInfo,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PostInfo {
+    /// 主题 id
+    pub thread_id: String,
+    /// 评论 id
+    pub post_id: String,
+    /// 内容，[`RichText`] 序列化后的 json 字符串
+    pub content: String,
+    /// 评论时间
+    pub date_time: DateTime<Utc>,
+}
+
+impl PostInfo {
+    /// 按照 [`RichText`] 解析内容
+    pub fn rich_content(&self) -> crate::Result<RichText> {
+        RichText::from_json(&self.content)
+    }
+}
+
+/// 论坛回复
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Reply {
+    #[serde_as(as = "DisplayFromStr")]
+    /// 频道 id
+    pub guild_id: GuildId,
+    #[serde_as(as = "DisplayFromStr")]
+    /// 子频道 id
+    pub channel_id: ChannelId,
+    /// 作者 id
+    pub author_id: String,
+    /// 回复内容
+    pub reply_info: ReplyInfo,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReplyInfo {
+    /// 主题 id
+    pub thread_id: String,
+    /// 评论 id
+    pub post_id: String,
+    /// 回复 id
+    pub reply_id: String,
+    /// 内容，[`RichText`] 序列化后的 json 字符串
+    pub content: String,
+    /// 回复时间
+    pub date_time: DateTime<Utc>,
+}
+
+impl ReplyInfo {
+    /// 按照 [`RichText`] 解析内容
+    pub fn rich_content(&self) -> crate::Result<RichText> {
+        RichText::from_json(&self.content)
+    }
+}
+
+/// 论坛发表的审核结果
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ForumAuditResult {
+    /// 审核任务 id
+    pub task_id: String,
+    #[serde_as(as = "DisplayFromStr")]
+    /// 频道 id
+    pub guild_id: GuildId,
+    #[serde_as(as = "DisplayFromStr")]
+    /// 子频道 id
+    pub channel_id: ChannelId,
+    /// 作者 id
+    pub author_id: String,
+    #[serde(default)]
+    /// 主题 id
+    pub thread_id: String,
+    #[serde(default)]
+    /// 评论 id
+    pub post_id: String,
+    #[serde(default)]
+    /// 回复 id
+    pub reply_id: String,
+    /// 审核的类型
+    pub r#type: ForumAuditType,
+    /// 审核结果，0 成功，1 失败
+    pub result: u32,
+    #[serde(default)]
+    /// 审核失败时的错误信息
+    pub err_msg: String,
+    #[serde(default)]
+    /// 审核时间
+    pub date_time: Option<DateTime<Utc>>,
+}
+
+impl ForumAuditResult {
+    pub fn is_passed(&self) -> bool {
+        self.result == 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize_repr, Deserialize_repr)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum ForumAuditType {
+    /// 帖子
+    Thread = 1,
+    /// 评论
+    Post = 2,
+    /// 回复
+    Reply = 3,
+    /// 尚未支持的
+    #[serde(other)]
+    Unsupported = u32::MAX,
+}
+
+/// 发表帖子的内容格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize_repr, Deserialize_repr, Default)]
+#[repr(u32)]
+pub enum ThreadFormat {
+    /// 普通文本
+    #[default]
+    Text = 1,
+    /// HTML
+    Html = 2,
+    /// Markdown
+    Markdown = 3,
+    /// [`RichText`] 序列化后的 json
+    Json = 4,
+}
+
+/// 发表帖子的结果，帖子需要审核，结果通过 FORUM_PUBLISH_AUDIT_RESULT 事件推送
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ThreadPublished {
+    /// 审核任务 id
+    pub task_id: String,
+    #[serde_as(as = "TimestampSeconds<i64, Flexible>")]
+    /// 发表时间
+    pub create_time: DateTime<Utc>,
+}
+
+/// 论坛的富文本内容
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RichText {
+    #[serde(default)]
+    /// 段落
+    pub paragraphs: Vec<Paragraph>,
+}
+
+impl RichText {
+    pub fn from_json(json: &str) -> crate::Result<Self> {
+        serde_json::from_str(json).map_err(crate::Error::context("parse rich text"))
+    }
+    /// 所有文本元素的内容，段落之间以换行分隔
+    pub fn plain_text(&self) -> String {
+        self.paragraphs
+            .iter()
+            .map(|paragraph| {
+                paragraph
+                    .elems
+                    .iter()
+                    .filter_map(|elem| elem.text.as_ref().map(|text| text.text.as_str()))
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// 富文本段落
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Paragraph {
+    #[serde(default)]
+    /// 元素列表
+    pub elems: Vec<Elem>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// 段落属性
+    pub props: Option<ParagraphProps>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ParagraphProps {
+    #[serde(default)]
+    /// 段落对齐方向
+    pub alignment: Alignment,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize_repr, Deserialize_repr, Default)]
+#[repr(u32)]
+pub enum Alignment {
+    /// 左对齐
+    #[default]
+    Left = 0,
+    /// 居中
+    Middle = 1,
+    /// 右对齐
+    Right = 2,
+}
+
+/// 富文本元素，`type` 对应的字段有值
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Elem {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// 文本
+    pub text: Option<TextElem>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// 图片
+    pub image: Option<ImageElem>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// 视频
+    pub video: Option<VideoElem>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// 链接
+    pub url: Option<UrlElem>,
+    /// 元素类型
+    pub r#type: ElemType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize_repr, Deserialize_repr)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum ElemType {
+    /// 文本
+    Text = 1,
+    /// 图片
+    Image = 2,
+    /// 视频
+    Video = 3,
+    /// 链接
+    Url = 4,
+    /// 尚未支持的
+    #[serde(other)]
+    Unsupported = u32::MAX,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TextElem {
+    /// 正文
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// 文本属性
+    pub props: Option<TextProps>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TextProps {
+    #[serde(default)]
+    /// 加粗
+    pub font_bold: bool,
+    #[serde(default)]
+    /// 斜体
+    pub italic: bool,
+    #[serde(default)]
+    /// 下划线
+    pub underline: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ImageElem {
+    /// 第三方图片链接
+    pub third_url: String,
+    #[serde(default)]
+    /// 宽度比例（缩放比，在屏幕里显示的比例）
+    pub width_percent: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct VideoElem {
+    /// 第三方视频文件链接
+    pub third_url: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct UrlElem {
+    /// 链接地址
+    pub url: String,
+    #[serde(default)]
+    /// 链接描述
+    pub desc: String,
+}
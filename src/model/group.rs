@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::{TimestampSeconds, formats::Flexible, serde_as};
+
+use super::{MessageAttachment, MessageId};
+
+/// 群聊成员，群聊场景下使用 openid 标识用户
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GroupMember {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// 用户在本群的 member_openid
+    pub member_openid: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub union_openid: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GroupMessageRecieved {
+    /// 平台方消息 id，可以用于被动消息发送
+    pub id: MessageId,
+    /// 群聊的 openid
+    pub group_openid: String,
+    #[serde(default)]
+    /// 消息内容
+    pub content: String,
+    /// 消息生产时间
+    pub timestamp: DateTime<Utc>,
+    /// 发送者
+    pub author: GroupMember,
+    #[serde(default)]
+    /// 富媒体文件附件
+    pub attachments: Vec<MessageAttachment>,
+}
+
+/// 机器人被添加/移出群聊，群管理员打开/关闭主动消息推送
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GroupOperation {
+    /// 群聊的 openid
+    pub group_openid: String,
+    /// 操作成员的 openid
+    pub op_member_openid: String,
+    #[serde_as(as = "TimestampSeconds<i64, Flexible>")]
+    /// 操作时间
+    pub timestamp: DateTime<Utc>,
+}
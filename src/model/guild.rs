@@ -16,14 +16,26 @@ pub struct Guild {
     pub icon: String,
     ///创建人用户ID
     pub owner_id: String,
-    ///当前人是否是创建人
+    #[serde(default)]
+    ///当前人是否是创建人，事件推送的频道对象中没有这个字段
     pub owner: bool,
     ///成员数
     pub member_count: i32,
     ///最大成员数
     pub max_members: i32,
+    #[serde(default)]
     ///描述
     pub description: String,
     ///加入时间
     pub joined_at: DateTime<Utc>,
 }
+
+/// 频道事件推送的频道对象
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GuildWithOpUser {
+    #[serde(flatten)]
+    pub guild: Guild,
+    #[serde(default)]
+    /// 操作人 id
+    pub op_user_id: Option<String>,
+}
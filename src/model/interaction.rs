@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use serde_with::{DisplayFromStr, serde_as};
+
+use super::{ChannelId, GuildId};
+
+/// 互动事件的场景
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum InteractionScene {
+    /// 频道
+    Guild,
+    /// 群聊
+    Group,
+    /// 单聊
+    C2c,
+    /// 尚未支持的
+    #[serde(other)]
+    Unsupported,
+}
+
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Interaction {
+    /// 平台方事件 id，用于回应互动
+    pub id: String,
+    #[serde(default)]
+    /// 机器人的 appid
+    pub application_id: Option<String>,
+    /// 互动类型，11 为消息按钮
+    pub r#type: u32,
+    #[serde(default)]
+    /// 互动的场景，较早的事件中没有这个字段，可以使用 [`Interaction::scene`]
+    pub scene: Option<InteractionScene>,
+    #[serde(default)]
+    /// 0 频道场景，1 群聊场景，2 单聊场景
+    pub chat_type: Option<u8>,
+    #[serde(default)]
+    /// 触发时间
+    pub timestamp: Option<DateTime<Utc>>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    /// 频道 id，仅频道场景有值
+    pub guild_id: Option<GuildId>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    /// 子频道 id，仅频道场景有值
+    pub channel_id: Option<ChannelId>,
+    #[serde(default)]
+    /// 单聊按钮触发的用户 openid，仅单聊场景有值
+    pub user_openid: Option<String>,
+    #[serde(default)]
+    /// 群的 openid，仅群聊场景有值
+    pub group_openid: Option<String>,
+    #[serde(default)]
+    /// 按钮触发用户在群里的 openid，仅群聊场景有值
+    pub group_member_openid: Option<String>,
+    /// 互动数据
+    pub data: InteractionData,
+    #[serde(default)]
+    pub version: u32,
+}
+
+impl Interaction {
+    /// 点击按钮的 button_id
+    pub fn button_id(&self) -> Option<&str> {
+        self.data.resolved.button_id.as_deref()
+    }
+    /// 按钮上设置的回调数据
+    pub fn button_data(&self) -> Option<&str> {
+        self.data.resolved.button_data.as_deref()
+    }
+    /// 互动的场景，没有 `scene` 字段时根据 `chat_type` 推断
+    pub fn scene(&self) -> InteractionScene {
+        match (self.scene, self.chat_type) {
+            (Some(scene), _) => scene,
+            (None, Some(0)) => InteractionScene::Guild,
+            (None, Some(1)) => InteractionScene::Group,
+            (None, Some(2)) => InteractionScene::C2c,
+            (None, _) => InteractionScene::Unsupported,
+        }
+    }
+    /// 触发互动的用户，频道场景下是用户 id，群聊和单聊场景下是 openid
+    pub fn user(&self) -> Option<&str> {
+        match self.scene() {
+            InteractionScene::Group => self.group_member_openid.as_deref(),
+            InteractionScene::C2c => self.user_openid.as_deref(),
+            _ => self.data.resolved.user_id.as_deref(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InteractionData {
+    /// 互动类型，11 为消息按钮
+    pub r#type: u32,
+    #[serde(default)]
+    pub resolved: InteractionResolved,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct InteractionResolved {
+    #[serde(default)]
+    /// 按钮的回调数据
+    pub button_data: Option<String>,
+    #[serde(default)]
+    /// 按钮 id
+    pub button_id: Option<String>,
+    #[serde(default)]
+    /// 操作的用户 id，仅频道场景有值
+    pub user_id: Option<String>,
+    #[serde(default)]
+    /// 操作按钮所在的消息 id，仅频道场景有值
+    pub message_id: Option<String>,
+    #[serde(default)]
+    /// 自定义菜单的 id
+    pub feature_id: Option<String>,
+}
+
+/// 回应互动的结果
+#[derive(Serialize_repr, Deserialize_repr, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum InteractionResult {
+    /// 成功
+    #[default]
+    Success = 0,
+    /// 操作失败
+    Failed = 1,
+    /// 操作频繁
+    TooFrequent = 2,
+    /// 重复操作
+    Duplicate = 3,
+    /// 没有权限
+    NoPermission = 4,
+    /// 仅管理员操作
+    AdminOnly = 5,
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+use super::MessageMedia;
+
+/// 富媒体文件类型
+#[derive(Serialize_repr, Deserialize_repr, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FileType {
+    /// 图片 png/jpg
+    Image = 1,
+    /// 视频 mp4
+    Video = 2,
+    /// 语音 silk
+    Voice = 3,
+    /// 文件（暂不开放）
+    File = 4,
+}
+
+/// 富媒体上传的返回
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Media {
+    /// 文件 id
+    pub file_uuid: String,
+    /// 文件信息，用于发消息接口的 media 字段使用
+    pub file_info: String,
+    /// 有效期，表示剩余多少秒到期，到期后 file_info 失效，当等于 0 时，表示可长期使用
+    pub ttl: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// 发送消息的唯一id，仅在 srv_send_msg 设置为 true 时返回
+    pub id: Option<String>,
+}
+
+impl From<Media> for MessageMedia {
+    fn from(media: Media) -> Self {
+        MessageMedia {
+            file_info: media.file_info,
+        }
+    }
+}
+
+impl From<&Media> for MessageMedia {
+    fn from(media: &Media) -> Self {
+        MessageMedia {
+            file_info: media.file_info.clone(),
+        }
+    }
+}
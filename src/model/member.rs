@@ -1,13 +1,15 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
 
-use super::user::User;
+use super::{GuildId, user::User};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Member {
     #[serde(skip_serializing_if = "Option::is_none")]
     /// 用户的频道基础信息，只有成员相关接口中会填充此信息
     pub user: Option<User>,
+    #[serde(default)]
     /// 用户的昵称
     pub nick: String,
     #[serde(default)]
@@ -17,11 +19,16 @@ pub struct Member {
     pub joined_at: DateTime<Utc>,
 }
 
+#[serde_as]
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct MemberWithGuildID {
     #[serde(flatten)]
     /// 成员
     pub member: Member,
+    #[serde_as(as = "DisplayFromStr")]
     /// 频道id
-    pub guild_id: String,
+    pub guild_id: GuildId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// 操作人 id，仅成员事件中有值
+    pub op_user_id: Option<String>,
 }
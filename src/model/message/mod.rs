@@ -1,7 +1,8 @@
 use super::*;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use serde_with::{DisplayFromStr, serde_as};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use serde_with::{DisplayFromStr, PickFirst, TimestampSeconds, formats::Flexible, serde_as};
 mod markdown;
 pub use markdown::*;
 pub use message_id::*;
@@ -77,6 +78,9 @@ pub struct MessageBotRecieved {
     #[serde_as(as = "Option<DisplayFromStr>")]
     /// 用于私信场景下识别真实的来源频道id
     pub src_guild_id: Option<u64>,
+    #[serde(default)]
+    /// 是否是私信消息
+    pub direct_message: bool,
 }
 
 impl From<MessageBotRecieved> for MessageAudited {
@@ -177,10 +181,63 @@ pub struct MessageSend<'a> {
     pub ark: Option<MessageArk>,
     pub image: Option<&'a str>,
     pub markdown: Option<MessageMarkdown>,
+    pub media: Option<MessageMedia>,
     pub msg_id: Option<MessageId>,
     pub event_id: Option<&'a str>,
 }
 
+impl MessageSend<'_> {
+    /// 群聊和单聊消息的类型，按照 media、markdown、ark、embed 的顺序推断
+    pub fn msg_type(&self) -> MessageType {
+        if self.media.is_some() {
+            MessageType::Media
+        } else if self.markdown.is_some() {
+            MessageType::Markdown
+        } else if self.ark.is_some() {
+            MessageType::Ark
+        } else if self.embed.is_some() {
+            MessageType::Embed
+        } else {
+            MessageType::Text
+        }
+    }
+}
+
+/// 群聊和单聊的消息类型
+#[derive(Serialize_repr, Deserialize_repr, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum MessageType {
+    /// 文本
+    #[default]
+    Text = 0,
+    /// markdown
+    Markdown = 2,
+    /// ark
+    Ark = 3,
+    /// embed
+    Embed = 4,
+    /// 富媒体
+    Media = 7,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MessageMedia {
+    /// 富媒体上传接口返回的 file_info
+    pub file_info: String,
+}
+
+/// 群聊和单聊消息发送成功的返回
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MessageSent {
+    /// 消息 id
+    pub id: MessageId,
+    #[serde(default)]
+    #[serde_as(as = "Option<PickFirst<(_, TimestampSeconds<i64, Flexible>)>>")]
+    /// 发送时间，平台返回的有时是 RFC3339 字符串，有时是秒级时间戳
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct MessageEmbed {
     /// 标题
@@ -1,15 +1,29 @@
+mod audio;
+mod c2c;
 mod channel;
+mod dms;
 mod emoji;
+mod forum;
+mod group;
 mod guild;
+mod interaction;
+mod media;
 mod member;
 mod message;
 mod message_reaction;
 mod role;
 mod user;
 
+pub use audio::*;
+pub use c2c::*;
 pub use channel::*;
+pub use dms::*;
 pub use emoji::*;
+pub use forum::*;
+pub use group::*;
 pub use guild::*;
+pub use interaction::*;
+pub use media::*;
 pub use member::*;
 pub use message::*;
 pub use message_reaction::*;
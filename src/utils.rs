@@ -23,6 +23,11 @@ pub fn verify_signature(bot_secret: &str, message: &[u8], signature: &Signature)
     vk.verify(message, signature).is_ok()
 }
 
+/// 签名的时间戳 `timestamp` 与当前时间 `now` 相差不超过 `tolerance` 秒
+pub fn verify_timestamp(timestamp: i64, now: i64, tolerance: u64) -> bool {
+    now.abs_diff(timestamp) <= tolerance
+}
+
 pub fn sign(bot_secret: &str, message: &[u8]) -> Signature {
     let (sk, _) = gen_ed25519_keys(bot_secret);
     sk.sign(message)
@@ -73,4 +78,19 @@ mod tests {
         assert!(verify_signature(bot_secret, message.as_bytes(), &signature));
         Ok(())
     }
+
+    #[test]
+    fn test_verify_timestamp() {
+        let now = 1728981195;
+        assert!(verify_timestamp(now, now, 300));
+        // edge of the window, both directions
+        assert!(verify_timestamp(now - 300, now, 300));
+        assert!(verify_timestamp(now + 300, now, 300));
+        // stale
+        assert!(!verify_timestamp(now - 301, now, 300));
+        // from the future
+        assert!(!verify_timestamp(now + 301, now, 300));
+        assert!(!verify_timestamp(i64::MIN, now, 300));
+        assert!(!verify_timestamp(i64::MAX, now, 300));
+    }
 }
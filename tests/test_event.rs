@@ -1 +1,959 @@
+use qqbot_sdk::event::model::{Event, EventEnvelope, GeneralPayload, InboundPayloadKind, Intents};
 
+fn dispatch(json: &str) -> Event {
+    dispatch_envelope(json).into_event()
+}
+
+fn dispatch_envelope(json: &str) -> EventEnvelope {
+    let payload = serde_json::from_str::<GeneralPayload>(json).unwrap();
+    match payload.into_inbound().unwrap() {
+        InboundPayloadKind::Dispatch(envelope) => envelope,
+        _ => panic!("expect dispatch payload"),
+    }
+}
+
+fn test_bot(secret: &str) -> qqbot_sdk::bot::Bot {
+    qqbot_sdk::bot::Bot::new(qqbot_sdk::bot::BotConfig {
+        app_id: String::new(),
+        secret: secret.to_owned(),
+        base_url: String::new(),
+    })
+}
+
+/// 启动一个依次下发 `events` 的事件流
+fn feed(bot: &qqbot_sdk::bot::Bot, name: &'static str, events: Vec<EventEnvelope>) {
+    bot.event_service()
+        .spawn(VecProvider(futures_util::stream::iter(events), name))
+        .unwrap();
+}
+
+/// 等待所有事件流结束，结束之前收到的事件都已经放进处理器的队列
+async fn wait_providers(bot: &qqbot_sdk::bot::Bot) {
+    while bot.event_service().is_running() {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+}
+
+/// 注册一个收集所有事件的处理器
+async fn collect(bot: &qqbot_sdk::bot::Bot) -> tokio::sync::mpsc::UnboundedReceiver<EventEnvelope> {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    bot.event_service()
+        .spawn_handler("collector", Collector(tx))
+        .await;
+    rx
+}
+
+struct VecProvider(
+    futures_util::stream::Iter<std::vec::IntoIter<EventEnvelope>>,
+    &'static str,
+);
+
+impl futures_util::Stream for VecProvider {
+    type Item = EventEnvelope;
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        std::pin::Pin::new(&mut self.0).poll_next(cx)
+    }
+}
+
+impl qqbot_sdk::event::EventStreamProvider for VecProvider {
+    fn name(&self) -> std::borrow::Cow<'static, str> {
+        self.1.into()
+    }
+}
+
+struct Collector(tokio::sync::mpsc::UnboundedSender<EventEnvelope>);
+
+impl qqbot_sdk::event::handler::EventHandler for Collector {
+    fn intents(&self) -> Intents {
+        Intents::all()
+    }
+    fn would_handle(&self, _event: &EventEnvelope, _bot: &qqbot_sdk::bot::Bot) -> bool {
+        true
+    }
+    async fn handle(
+        &self,
+        event: EventEnvelope,
+        _bot: &qqbot_sdk::bot::Bot,
+    ) -> qqbot_sdk::Result<()> {
+        let _ = self.0.send(event);
+        Ok(())
+    }
+}
+
+#[test]
+fn deserialize_group_and_c2c_events() {
+    let event = dispatch(
+        r#"{"op":0,"id":"GROUP_AT_MESSAGE_CREATE:abc","t":"GROUP_AT_MESSAGE_CREATE","s":2,"d":{"author":{"id":"E4F4AEA33253A2797FB897C50B81D7ED","member_openid":"E4F4AEA33253A2797FB897C50B81D7ED"},"content":" 123","group_id":"C9F778FE6ADF9D1D1DBE395BF744A33A","group_openid":"C9F778FE6ADF9D1D1DBE395BF744A33A","id":"ROBOT1.0_2PuCzOzvjqQdUfA5sbESQ!!","timestamp":"2023-11-06T13:37:18+08:00"}}"#,
+    );
+    let Event::GroupAtMessageCreate(message) = event else {
+        panic!("unexpect event: {:?}", event);
+    };
+    assert_eq!(message.group_openid, "C9F778FE6ADF9D1D1DBE395BF744A33A");
+    assert_eq!(
+        message.author.member_openid,
+        "E4F4AEA33253A2797FB897C50B81D7ED"
+    );
+
+    let event = dispatch(
+        r#"{"op":0,"id":"C2C_MESSAGE_CREATE:abc","t":"C2C_MESSAGE_CREATE","s":3,"d":{"author":{"user_openid":"E4F4AEA33253A2797FB897C50B81D7ED"},"content":"123","id":"ROBOT1.0_2PuCzOzvjqQdUfA5sbESQ!!","timestamp":"2023-11-06T13:37:18+08:00"}}"#,
+    );
+    let Event::C2cMessageCreate(message) = event else {
+        panic!("unexpect event: {:?}", event);
+    };
+    assert_eq!(
+        message.author.user_openid,
+        "E4F4AEA33253A2797FB897C50B81D7ED"
+    );
+
+    let event = dispatch(
+        r#"{"op":0,"id":"GROUP_ADD_ROBOT:abc","t":"GROUP_ADD_ROBOT","d":{"group_openid":"C9F778FE6ADF9D1D1DBE395BF744A33A","op_member_openid":"E4F4AEA33253A2797FB897C50B81D7ED","timestamp":1699250238}}"#,
+    );
+    let Event::GroupAddRobot(operation) = event else {
+        panic!("unexpect event: {:?}", event);
+    };
+    assert_eq!(operation.timestamp.timestamp(), 1699250238);
+
+    let event = dispatch(
+        r#"{"op":0,"id":"FRIEND_ADD:abc","t":"FRIEND_ADD","d":{"openid":"E4F4AEA33253A2797FB897C50B81D7ED","timestamp":1699250238}}"#,
+    );
+    assert!(matches!(event, Event::FriendAdd(_)));
+}
+
+#[test]
+fn deserialize_interaction_event() {
+    let event = dispatch(
+        r#"{"op":0,"id":"INTERACTION_CREATE:abc","t":"INTERACTION_CREATE","s":4,"d":{"application_id":"102005211","chat_type":1,"data":{"resolved":{"button_data":"回调数据","button_id":"1"},"type":11},"group_member_openid":"E4F4AEA33253A2797FB897C50B81D7ED","group_openid":"C9F778FE6ADF9D1D1DBE395BF744A33A","id":"50e9c5f7-1a8b-4b8c-9d46-d1f1c9d1a2b3","scene":"group","timestamp":"2023-11-06T14:33:43+08:00","type":11,"version":1}}"#,
+    );
+    assert_eq!(
+        event.intents(),
+        qqbot_sdk::event::model::Intents::INTERACTION
+    );
+    let Event::InteractionCreate(interaction) = event else {
+        panic!("unexpect event: {:?}", event);
+    };
+    assert_eq!(
+        interaction.scene(),
+        qqbot_sdk::model::InteractionScene::Group
+    );
+    assert_eq!(interaction.button_id(), Some("1"));
+    assert_eq!(interaction.button_data(), Some("回调数据"));
+    assert_eq!(interaction.user(), Some("E4F4AEA33253A2797FB897C50B81D7ED"));
+
+    // older interaction events come without `scene`
+    let event = dispatch(
+        r#"{"op":0,"id":"INTERACTION_CREATE:def","t":"INTERACTION_CREATE","d":{"application_id":"102005211","chat_type":2,"data":{"resolved":{"button_id":"2"},"type":11},"id":"60e9c5f7-1a8b-4b8c-9d46-d1f1c9d1a2b3","type":11,"user_openid":"E4F4AEA33253A2797FB897C50B81D7ED"}}"#,
+    );
+    let Event::InteractionCreate(interaction) = event else {
+        panic!("unexpect event: {:?}", event);
+    };
+    assert!(interaction.scene.is_none());
+    assert_eq!(interaction.scene(), qqbot_sdk::model::InteractionScene::C2c);
+    assert_eq!(interaction.user(), Some("E4F4AEA33253A2797FB897C50B81D7ED"));
+}
+
+#[test]
+fn deserialize_guild_and_channel_events() {
+    let event = dispatch(
+        r#"{"op":0,"id":"GUILD_CREATE:abc","t":"GUILD_CREATE","s":5,"d":{"description":"","icon":"http://thirdqq.qlogo.cn/0","id":"2359165788034567890","joined_at":"2021-12-21T14:18:10+08:00","max_members":300,"member_count":4,"name":"测试频道","op_user_id":"14813347163423456789","owner_id":"14813347163423456789"}}"#,
+    );
+    let Event::GuildCreate(guild) = event else {
+        panic!("unexpect event: {:?}", event);
+    };
+    assert_eq!(guild.guild.id, 2359165788034567890);
+    assert_eq!(guild.op_user_id.as_deref(), Some("14813347163423456789"));
+
+    let event = dispatch(
+        r#"{"op":0,"id":"CHANNEL_DELETE:abc","t":"CHANNEL_DELETE","s":6,"d":{"guild_id":"2359165788034567890","id":"1402417","name":"测试子频道","op_user_id":"14813347163423456789","owner_id":"14813347163423456789","sub_type":0,"type":0}}"#,
+    );
+    assert_eq!(event.intents(), qqbot_sdk::event::model::Intents::GUILDS);
+    let Event::ChannelDelete(channel) = event else {
+        panic!("unexpect event: {:?}", event);
+    };
+    assert_eq!(channel.channel.id, 1402417);
+    assert_eq!(channel.channel.guild_id, 2359165788034567890);
+}
+
+#[test]
+fn deserialize_guild_member_events() {
+    let event = dispatch(
+        r#"{"op":0,"id":"GUILD_MEMBER_ADD:abc","t":"GUILD_MEMBER_ADD","s":7,"d":{"guild_id":"2359165788034567890","joined_at":"2021-12-21T14:18:10+08:00","nick":"","op_user_id":"14813347163423456789","roles":["1"],"user":{"avatar":"http://thirdqq.qlogo.cn/0","bot":false,"id":"14813347163423456789","username":"新成员"}}}"#,
+    );
+    assert_eq!(
+        event.intents(),
+        qqbot_sdk::event::model::Intents::GUILD_MEMBERS
+    );
+    let Event::GuildMemberAdd(member) = event else {
+        panic!("unexpect event: {:?}", event);
+    };
+    assert_eq!(member.guild_id, 2359165788034567890);
+    assert_eq!(member.op_user_id.as_deref(), Some("14813347163423456789"));
+    assert_eq!(
+        member.member.user.as_ref().map(|user| user.id),
+        Some(14813347163423456789)
+    );
+}
+
+#[test]
+fn deserialize_direct_message_event() {
+    let event = dispatch(
+        r#"{"op":0,"id":"DIRECT_MESSAGE_CREATE:abc","t":"DIRECT_MESSAGE_CREATE","s":8,"d":{"author":{"avatar":"http://thirdqq.qlogo.cn/0","id":"14813347163423456789","username":"用户"},"channel_id":"1402417","content":"私信","direct_message":true,"guild_id":"9876543210","id":"08e092eeb983afef9e0110f1d1b7fd0138ac0248c7a9ed9c06","member":{"joined_at":"2022-03-28T13:46:06+08:00"},"seq":3,"seq_in_channel":"3","src_guild_id":"2359165788034567890","timestamp":"2022-03-28T13:46:15+08:00"}}"#,
+    );
+    assert_eq!(
+        event.intents(),
+        qqbot_sdk::event::model::Intents::DIRECT_MESSAGE
+    );
+    let Event::DirectMessageCreate(message) = event else {
+        panic!("unexpect event: {:?}", event);
+    };
+    assert!(message.direct_message);
+    assert_eq!(message.guild_id, 9876543210);
+    assert_eq!(message.src_guild_id, Some(2359165788034567890));
+}
+
+#[test]
+fn deserialize_forum_events() {
+    let event = dispatch(
+        r#"{"op":0,"id":"FORUM_THREAD_CREATE:abc","t":"FORUM_THREAD_CREATE","s":9,"d":{"author_id":"14813347163423456789","channel_id":"1402417","guild_id":"2359165788034567890","thread_info":{"content":"{\"paragraphs\":[{\"elems\":[{\"text\":{\"text\":\"正文\"},\"type\":1},{\"image\":{\"third_url\":\"https://example.com/a.png\",\"width_percent\":1.0},\"type\":2}],\"props\":{}},{\"elems\":[{\"text\":{\"text\":\"第二段\",\"props\":{\"font_bold\":true}},\"type\":1}]}]}","date_time":"2022-03-29T10:47:36+08:00","thread_id":"B_a2ec3d621b6e0c0e","title":"{\"paragraphs\":[{\"elems\":[{\"text\":{\"text\":\"标题\"},\"type\":1}],\"props\":{}}]}"}}}"#,
+    );
+    assert_eq!(
+        event.intents(),
+        qqbot_sdk::event::model::Intents::FORUMS_EVENT
+    );
+    let Event::ForumThreadCreate(thread) = event else {
+        panic!("unexpect event: {:?}", event);
+    };
+    let content = thread.thread_info.rich_content().unwrap();
+    assert_eq!(content.plain_text(), "正文\n第二段");
+
+    let event = dispatch(
+        r#"{"op":0,"id":"FORUM_PUBLISH_AUDIT_RESULT:abc","t":"FORUM_PUBLISH_AUDIT_RESULT","s":10,"d":{"author_id":"14813347163423456789","channel_id":"1402417","date_time":"2022-03-29T10:48:01+08:00","guild_id":"2359165788034567890","result":0,"task_id":"16c2a1ca-2e6d-4c16-a9e1-2b1d1f4e1b9f","thread_id":"B_a2ec3d621b6e0c0e","type":1}}"#,
+    );
+    let Event::ForumPublishAuditResult(result) = event else {
+        panic!("unexpect event: {:?}", event);
+    };
+    assert!(result.is_passed());
+}
+
+#[test]
+fn deserialize_audio_events() {
+    let event = dispatch(
+        r#"{"op":0,"id":"AUDIO_START:abc","t":"AUDIO_START","s":11,"d":{"audio_url":"https://example.com/a.mp3","channel_id":"1402417","guild_id":"2359165788034567890","text":"简单爱-周杰伦"}}"#,
+    );
+    assert_eq!(
+        event.intents(),
+        qqbot_sdk::event::model::Intents::AUDIO_ACTION
+    );
+    let Event::AudioStart(action) = event else {
+        panic!("unexpect event: {:?}", event);
+    };
+    assert_eq!(action.text.as_deref(), Some("简单爱-周杰伦"));
+
+    let event = dispatch(
+        r#"{"op":0,"id":"AUDIO_OR_LIVE_CHANNEL_MEMBER_ENTER:abc","t":"AUDIO_OR_LIVE_CHANNEL_MEMBER_ENTER","s":12,"d":{"channel_id":"1402417","channel_type":2,"guild_id":"2359165788034567890","user_id":"14813347163423456789"}}"#,
+    );
+    let Event::AudioOrLiveChannelMemberEnter(member) = event else {
+        panic!("unexpect event: {:?}", event);
+    };
+    assert_eq!(member.channel_type, 2);
+}
+
+#[test]
+fn every_known_kind_deserializes() {
+    // one sample per data type, every kind must accept one of them
+    let samples = [
+        r#"{"description":"","icon":"","id":"2359165788034567890","joined_at":"2021-12-21T14:18:10+08:00","max_members":300,"member_count":4,"name":"测试频道","op_user_id":"1","owner_id":"1"}"#,
+        r#"{"guild_id":"2359165788034567890","id":"1402417","name":"测试子频道","op_user_id":"1","owner_id":"1","sub_type":0,"type":0}"#,
+        r#"{"guild_id":"2359165788034567890","joined_at":"2021-12-21T14:18:10+08:00","nick":"","op_user_id":"1","roles":["1"],"user":{"avatar":"","bot":false,"id":"1","username":"新成员"}}"#,
+        r#"{"author":{"avatar":"","id":"1","username":"用户"},"channel_id":"1402417","content":"私信","direct_message":true,"guild_id":"9876543210","id":"08e092eeb983afef9e0110f1d1b7fd0138ac0248c7a9ed9c06","member":{"joined_at":"2022-03-28T13:46:06+08:00"},"seq":3,"seq_in_channel":"3","src_guild_id":"2359165788034567890","timestamp":"2022-03-28T13:46:15+08:00"}"#,
+        r#"{"message":{"author":{"avatar":"","id":"1","username":"用户"},"channel_id":"1402417","guild_id":"2359165788034567890","id":"08e092eeb983afef9e0110f1d1b7fd0138ac0248c7a9ed9c06"},"op_user":{"id":"1"}}"#,
+        r#"{"audit_id":"1","guild_id":"2359165788034567890","channel_id":"1402417","audit_time":"2022-03-28T13:46:15+08:00","create_time":"2022-03-28T13:46:15+08:00","seq_in_channel":"1"}"#,
+        r#"{"channel_id":"12239797","emoji":{"id":"318","type":1},"guild_id":"17480253631046435465","target":{"id":"08898585b282c295cbf20110b587eb05383d48c894e3a306","type":0},"user_id":"17714500961343160758"}"#,
+        r#"{"author_id":"1","channel_id":"1402417","guild_id":"2359165788034567890","thread_info":{"content":"","date_time":"2022-03-29T10:47:36+08:00","thread_id":"B_a2ec3d621b6e0c0e","title":""}}"#,
+        r#"{"author_id":"1","channel_id":"1402417","guild_id":"2359165788034567890","post_info":{"content":"","date_time":"2022-03-29T10:47:36+08:00","post_id":"1","thread_id":"B_a2ec3d621b6e0c0e"}}"#,
+        r#"{"author_id":"1","channel_id":"1402417","guild_id":"2359165788034567890","reply_info":{"content":"","date_time":"2022-03-29T10:47:36+08:00","post_id":"1","reply_id":"2","thread_id":"B_a2ec3d621b6e0c0e"}}"#,
+        r#"{"author_id":"1","channel_id":"1402417","date_time":"2022-03-29T10:48:01+08:00","guild_id":"2359165788034567890","result":0,"task_id":"1","thread_id":"B_a2ec3d621b6e0c0e","type":1}"#,
+        r#"{"audio_url":"https://example.com/a.mp3","channel_id":"1402417","guild_id":"2359165788034567890","text":"简单爱-周杰伦"}"#,
+        r#"{"channel_id":"1402417","channel_type":2,"guild_id":"2359165788034567890","user_id":"1"}"#,
+        r#"{"author":{"id":"1","member_openid":"1"},"content":"123","group_id":"1","group_openid":"1","id":"1","timestamp":"2023-11-06T13:37:18+08:00"}"#,
+        r#"{"author":{"user_openid":"1"},"content":"123","id":"1","timestamp":"2023-11-06T13:37:18+08:00"}"#,
+        r#"{"group_openid":"1","op_member_openid":"1","timestamp":1699250238}"#,
+        r#"{"openid":"1","timestamp":1699250238}"#,
+        r#"{"application_id":"102005211","chat_type":1,"data":{"resolved":{"button_id":"1"},"type":11},"group_member_openid":"1","group_openid":"1","id":"1","scene":"group","timestamp":"2023-11-06T14:33:43+08:00","type":11,"version":1}"#,
+    ];
+    for kind in qqbot_sdk::event::kind::ALL {
+        assert!(Event::is_known_kind(kind));
+        let parsed = samples.iter().find_map(|data| {
+            let event = dispatch(&format!(
+                r#"{{"op":0,"id":"{kind}:abc","t":"{kind}","d":{data}}}"#
+            ));
+            (!matches!(event, Event::Unknown(_))).then_some(event)
+        });
+        let Some(event) = parsed else {
+            panic!("no sample deserializes to {kind}");
+        };
+        assert_eq!(event.kind(), *kind);
+    }
+}
+
+#[tokio::test]
+async fn unknown_and_custom_events() {
+    let event = dispatch(
+        r#"{"op":0,"id":"SUBSCRIBE_MESSAGE_STATUS:abc","t":"SUBSCRIBE_MESSAGE_STATUS","s":13,"d":{"result":1,"scene":"c2c"}}"#,
+    );
+    let Event::Unknown(unknown) = &event else {
+        panic!("unexpect event: {:?}", event);
+    };
+    assert_eq!(unknown.kind, "SUBSCRIBE_MESSAGE_STATUS");
+    assert_eq!(unknown.data["scene"], "c2c");
+    assert!(!Event::is_known_kind("SUBSCRIBE_MESSAGE_STATUS"));
+    assert!(Event::is_known_kind("GROUP_AT_MESSAGE_CREATE"));
+
+    // a known kind with malformed data falls back to unknown as well
+    let malformed = dispatch(
+        r#"{"op":0,"id":"GROUP_AT_MESSAGE_CREATE:abc","t":"GROUP_AT_MESSAGE_CREATE","s":14,"d":{"content":1}}"#,
+    );
+    let Event::Unknown(unknown) = &malformed else {
+        panic!("unexpect event: {:?}", malformed);
+    };
+    assert_eq!(unknown.kind, "GROUP_AT_MESSAGE_CREATE");
+    assert_eq!(unknown.data["content"], 1);
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct SubscribeMessageStatus {
+        result: u32,
+        scene: String,
+    }
+    let registry = qqbot_sdk::event::custom::CustomEventRegistry::new();
+    registry
+        .register::<SubscribeMessageStatus>("SUBSCRIBE_MESSAGE_STATUS")
+        .await;
+    let event = registry.resolve(event).await;
+    let Event::Custom(custom) = &event else {
+        panic!("unexpect event: {:?}", event);
+    };
+    assert_eq!(
+        custom.downcast_ref::<SubscribeMessageStatus>(),
+        Some(&SubscribeMessageStatus {
+            result: 1,
+            scene: "c2c".to_owned()
+        })
+    );
+}
+
+#[test]
+fn event_envelope_metadata() {
+    let envelope = dispatch_envelope(
+        r#"{"op":0,"id":"GROUP_ADD_ROBOT:f2b5b3b7-fe32-4c1b-a4f3-3e9c7f1f4a5b","t":"GROUP_ADD_ROBOT","s":14,"d":{"group_openid":"C9F778FE6ADF9D1D1DBE395BF744A33A","op_member_openid":"E4F4AEA33253A2797FB897C50B81D7ED","timestamp":1699250238}}"#,
+    );
+    assert_eq!(
+        envelope.event_id(),
+        Some("GROUP_ADD_ROBOT:f2b5b3b7-fe32-4c1b-a4f3-3e9c7f1f4a5b")
+    );
+    assert_eq!(envelope.seq, Some(14));
+    assert!(matches!(*envelope, Event::GroupAddRobot(_)));
+}
+
+#[tokio::test]
+async fn record_and_replay_payloads() {
+    use futures_util::StreamExt;
+    use qqbot_sdk::event::implement::replay::{EventRecorder, ReplayProvider, ReplaySpeed};
+
+    let path = std::env::temp_dir().join(format!("qqbot-replay-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let first = r#"{"op":0,"id":"FRIEND_ADD:1","t":"FRIEND_ADD","s":1,"d":{"openid":"E4F4AEA33253A2797FB897C50B81D7ED","timestamp":1699250238}}"#;
+    let second = r#"{"op":0,"id":"SOME_NEW_EVENT:2","t":"SOME_NEW_EVENT","s":2,"d":{"foo":"bar"}}"#;
+    let bot = test_bot("");
+    let recorder = EventRecorder::create(&path).await.unwrap();
+    assert!(bot.event_service().set_recorder(recorder.clone()).is_none());
+    // the duplicate is recorded as well, it is skipped after recording
+    let events = vec![
+        dispatch_envelope(first),
+        dispatch_envelope(second),
+        dispatch_envelope(first),
+    ];
+    let first = events[0].clone();
+    feed(&bot, "gateway", events);
+    wait_providers(&bot).await;
+    assert!(bot.event_service().remove_recorder().is_some());
+    recorder.flush().await.unwrap();
+
+    let replayed = ReplayProvider::open(&path, ReplaySpeed::AsFastAsPossible)
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(replayed.len(), 3);
+    assert_eq!(replayed[0].id, "FRIEND_ADD:1");
+    assert_eq!(replayed[0].seq, Some(1));
+    assert_eq!(replayed[0].received_at, first.received_at);
+    assert!(matches!(replayed[0].event, Event::FriendAdd(_)));
+    assert_eq!(replayed[1].id, "SOME_NEW_EVENT:2");
+    assert!(
+        matches!(&replayed[1].event, Event::Unknown(unknown) if unknown.kind == "SOME_NEW_EVENT")
+    );
+    assert_eq!(replayed[2].id, "FRIEND_ADD:1");
+}
+
+fn friend_add(id: &str) -> EventEnvelope {
+    dispatch_envelope(&format!(
+        r#"{{"op":0,"id":"{id}","t":"FRIEND_ADD","d":{{"openid":"E4F4AEA33253A2797FB897C50B81D7ED","timestamp":1699250238}}}}"#
+    ))
+}
+
+#[tokio::test]
+async fn dedup_events_across_providers() {
+    let bot = test_bot("");
+    let mut rx = collect(&bot).await;
+    let webhook = vec![friend_add("FRIEND_ADD:1"), friend_add("FRIEND_ADD:2")];
+    let gateway = vec![friend_add("FRIEND_ADD:2"), friend_add("FRIEND_ADD:3")];
+    feed(&bot, "webhook", webhook);
+    feed(&bot, "gateway", gateway);
+
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        let envelope = tokio::time::timeout(std::time::Duration::from_secs(1), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        ids.push(envelope.id);
+    }
+    ids.sort();
+    assert_eq!(ids, ["FRIEND_ADD:1", "FRIEND_ADD:2", "FRIEND_ADD:3"]);
+    assert!(
+        tokio::time::timeout(std::time::Duration::from_millis(100), rx.recv())
+            .await
+            .is_err()
+    );
+}
+
+struct Gated {
+    gate: std::sync::Arc<tokio::sync::Semaphore>,
+    tx: tokio::sync::mpsc::UnboundedSender<EventEnvelope>,
+}
+
+impl qqbot_sdk::event::handler::EventHandler for Gated {
+    fn intents(&self) -> Intents {
+        Intents::all()
+    }
+    fn would_handle(&self, _event: &EventEnvelope, _bot: &qqbot_sdk::bot::Bot) -> bool {
+        true
+    }
+    async fn handle(
+        &self,
+        event: EventEnvelope,
+        _bot: &qqbot_sdk::bot::Bot,
+    ) -> qqbot_sdk::Result<()> {
+        self.gate.acquire().await.unwrap().forget();
+        let _ = self.tx.send(event);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn lagging_handler_keeps_running() {
+    use qqbot_sdk::event::{handler::HandlerOptions, queue::OverflowPolicy};
+
+    let bot = test_bot("");
+    let gate = std::sync::Arc::new(tokio::sync::Semaphore::new(0));
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    bot.event_service()
+        .spawn_handler_with_options(
+            "gated",
+            Gated {
+                gate: gate.clone(),
+                tx,
+            },
+            HandlerOptions::default()
+                .with_queue_size(1)
+                .with_overflow_policy(OverflowPolicy::Reject),
+        )
+        .await;
+    let burst = (0..4)
+        .map(|i| friend_add(&format!("FRIEND_ADD:{i}")))
+        .collect::<Vec<_>>();
+    feed(&bot, "burst", burst);
+    wait_providers(&bot).await;
+    let stats = bot
+        .event_service()
+        .handler_stats(&"gated".into())
+        .await
+        .unwrap();
+    assert!(stats.rejected() >= 2);
+    assert_eq!(stats.pushed() + stats.rejected(), 4);
+
+    gate.add_permits(16);
+    feed(&bot, "after", vec![friend_add("FRIEND_ADD:after")]);
+    let mut last = None;
+    for _ in 0..stats.pushed() + 1 {
+        last = tokio::time::timeout(std::time::Duration::from_secs(1), rx.recv())
+            .await
+            .unwrap();
+    }
+    assert_eq!(last.unwrap().id, "FRIEND_ADD:after");
+}
+
+#[tokio::test]
+async fn lagging_handler_does_not_block_others() {
+    use qqbot_sdk::event::handler::HandlerOptions;
+
+    let bot = test_bot("");
+    let gate = std::sync::Arc::new(tokio::sync::Semaphore::new(0));
+    let (stuck_tx, _stuck_rx) = tokio::sync::mpsc::unbounded_channel();
+    bot.event_service()
+        .spawn_handler_with_options(
+            "stuck",
+            Gated {
+                gate: gate.clone(),
+                tx: stuck_tx,
+            },
+            HandlerOptions::default().with_queue_size(1),
+        )
+        .await;
+    let mut rx = collect(&bot).await;
+    let burst = (0..8)
+        .map(|i| friend_add(&format!("FRIEND_ADD:{i}")))
+        .collect::<Vec<_>>();
+    feed(&bot, "burst", burst);
+    for i in 0..8 {
+        let envelope = tokio::time::timeout(std::time::Duration::from_secs(1), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(envelope.id, format!("FRIEND_ADD:{i}"));
+    }
+    let stats = bot
+        .event_service()
+        .handler_stats(&"stuck".into())
+        .await
+        .unwrap();
+    // besides the queued one, the stuck handler holds at most two events: one handling, one waiting
+    assert_eq!(stats.pushed(), 8);
+    assert!(stats.dropped() >= 5);
+}
+
+/// 事件 id 的格式为 `key:name:delay_ms`
+struct Sleeper(tokio::sync::mpsc::UnboundedSender<String>);
+
+impl qqbot_sdk::event::handler::EventHandler for Sleeper {
+    fn intents(&self) -> Intents {
+        Intents::all()
+    }
+    fn would_handle(&self, _event: &EventEnvelope, _bot: &qqbot_sdk::bot::Bot) -> bool {
+        true
+    }
+    async fn handle(
+        &self,
+        event: EventEnvelope,
+        _bot: &qqbot_sdk::bot::Bot,
+    ) -> qqbot_sdk::Result<()> {
+        let delay = event.id.rsplit(':').next().unwrap().parse().unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+        let _ = self.0.send(event.id.clone());
+        Ok(())
+    }
+}
+
+// the clock only advances when every task is idle, so the handlers finish in the order of their delays
+#[tokio::test(start_paused = true)]
+async fn concurrent_handler_keeps_per_key_order() {
+    use qqbot_sdk::event::handler::{HandlerOptions, OrderingKey};
+
+    let bot = test_bot("");
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    bot.event_service()
+        .spawn_handler_with_options(
+            "sleeper",
+            Sleeper(tx),
+            HandlerOptions::default()
+                .with_max_in_flight(4)
+                .with_ordering_key(OrderingKey::new(|envelope| {
+                    envelope.id.split(':').next().map(str::to_owned)
+                }))
+                .with_timeout(std::time::Duration::from_millis(500)),
+        )
+        .await;
+    let events = ["a:1:200", "b:1:10", "a:2:10", "c:1:5000", "b:2:10"]
+        .into_iter()
+        .map(friend_add)
+        .collect::<Vec<_>>();
+    feed(&bot, "test", events);
+    let mut finished = Vec::new();
+    while let Ok(Some(id)) =
+        tokio::time::timeout(std::time::Duration::from_secs(1), rx.recv()).await
+    {
+        finished.push(id);
+    }
+    // c:1 times out, the others run concurrently across keys and in order within a key
+    assert_eq!(finished, ["b:1:10", "b:2:10", "a:1:200", "a:2:10"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn waiting_events_do_not_take_in_flight_slots() {
+    use qqbot_sdk::event::handler::{HandlerOptions, OrderingKey};
+
+    let bot = test_bot("");
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    bot.event_service()
+        .spawn_handler_with_options(
+            "sleeper",
+            Sleeper(tx),
+            HandlerOptions::default()
+                .with_max_in_flight(2)
+                .with_ordering_key(OrderingKey::new(|envelope| {
+                    envelope.id.split(':').next().map(str::to_owned)
+                })),
+        )
+        .await;
+    let events = ["a:1:100", "a:2:10", "b:1:10"]
+        .into_iter()
+        .map(friend_add)
+        .collect::<Vec<_>>();
+    feed(&bot, "test", events);
+    let mut finished = Vec::new();
+    let started = tokio::time::Instant::now();
+    while let Ok(Some(id)) =
+        tokio::time::timeout(std::time::Duration::from_secs(1), rx.recv()).await
+    {
+        finished.push((id, started.elapsed().as_millis()));
+    }
+    // a:2 waits for a:1 without holding the second slot, so b:1 runs right away
+    assert_eq!(
+        finished,
+        [
+            ("b:1:10".to_string(), 10),
+            ("a:1:100".to_string(), 100),
+            ("a:2:10".to_string(), 110),
+        ]
+    );
+}
+
+struct Flaky(tokio::sync::mpsc::UnboundedSender<String>);
+
+impl qqbot_sdk::event::handler::EventHandler for Flaky {
+    fn intents(&self) -> Intents {
+        Intents::all()
+    }
+    fn would_handle(&self, event: &EventEnvelope, _bot: &qqbot_sdk::bot::Bot) -> bool {
+        assert!(!event.id.starts_with("filter-panic"), "would_handle panic");
+        true
+    }
+    async fn handle(
+        &self,
+        event: EventEnvelope,
+        _bot: &qqbot_sdk::bot::Bot,
+    ) -> qqbot_sdk::Result<()> {
+        if event.id.starts_with("panic") {
+            panic!("handle panic");
+        }
+        if event.id.starts_with("fail") {
+            return Err(qqbot_sdk::Error::unexpected("handle failed"));
+        }
+        let _ = self.0.send(event.id.clone());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn supervise_panicking_handler() {
+    use qqbot_sdk::event::handler::{HandlerError, HandlerOptions, RestartBackoff};
+
+    let bot = test_bot("");
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let (error_tx, mut error_rx) = tokio::sync::mpsc::unbounded_channel();
+    bot.event_service()
+        .spawn_handler_with_options(
+            "flaky",
+            Flaky(tx),
+            HandlerOptions::default()
+                .with_restart_backoff(RestartBackoff {
+                    initial: std::time::Duration::from_millis(10),
+                    max: std::time::Duration::from_millis(50),
+                })
+                .with_on_error(move |failure| {
+                    let panicked = matches!(failure.error, HandlerError::Panicked(_));
+                    let _ = error_tx.send((
+                        failure.handler.clone(),
+                        failure.event.id.clone(),
+                        panicked,
+                    ));
+                }),
+        )
+        .await;
+    let events = ["panic:1", "fail:1", "filter-panic:1", "ok:1"]
+        .into_iter()
+        .map(friend_add)
+        .collect::<Vec<_>>();
+    feed(&bot, "test", events);
+    let handled = tokio::time::timeout(std::time::Duration::from_secs(1), rx.recv())
+        .await
+        .unwrap();
+    assert_eq!(handled.unwrap(), "ok:1");
+    let mut errors = Vec::new();
+    while let Ok(error) = error_rx.try_recv() {
+        errors.push(error);
+    }
+    assert_eq!(
+        errors,
+        [
+            ("flaky".into(), "panic:1".to_string(), true),
+            ("flaky".into(), "fail:1".to_string(), false),
+            ("flaky".into(), "filter-panic:1".to_string(), true),
+        ]
+    );
+}
+
+fn message_create(id: &str, author: &str, author_bot: bool, mentions: &str) -> EventEnvelope {
+    dispatch_envelope(&format!(
+        r#"{{"op":0,"id":"{id}","t":"MESSAGE_CREATE","d":{{"author":{{"id":"{author}","username":"u","bot":{author_bot}}},"channel_id":"100","guild_id":"200","content":"hi","id":"m","mentions":[{mentions}],"seq_in_channel":"1","timestamp":"2022-03-28T13:46:15+08:00"}}}}"#
+    ))
+}
+
+#[tokio::test]
+async fn filter_messages() {
+    use qqbot_sdk::event::filter::{EventFilter, EventFilterExt, on_message};
+
+    let bot = test_bot("");
+    let me = r#"{"id":"42","username":"me","bot":true}"#;
+    let plain = message_create("a", "1", false, "");
+    let mentioned = message_create("b", "1", false, me);
+    let from_bot = message_create("c", "2", true, me);
+
+    let filter = on_message()
+        .in_guild(200)
+        .in_channel(100)
+        .not_bot()
+        .mentions_me();
+    // the bot itself is unknown until `about_me` is called
+    assert!(!filter.matches(&mentioned, &bot));
+    bot.cache().cache_me(serde_json::from_str(me).unwrap());
+    assert!(!filter.matches(&plain, &bot));
+    assert!(filter.matches(&mentioned, &bot));
+    assert!(!filter.matches(&from_bot, &bot));
+    assert!(!on_message().in_guild(201).matches(&plain, &bot));
+    assert!(!on_message().matches(&friend_add("FRIEND_ADD:1"), &bot));
+
+    let from_user = on_message()
+        .from_user(2)
+        .or(on_message().from_user(1).not());
+    assert!(from_user.matches(&from_bot, &bot));
+    assert!(!from_user.matches(&plain, &bot));
+}
+
+#[tokio::test]
+async fn subscribe_typed_events() {
+    use qqbot_sdk::event::kind;
+
+    let bot = test_bot("");
+    let mut messages = bot.event_service().subscribe::<kind::MessageCreate>().await;
+    let events = vec![
+        friend_add("FRIEND_ADD:1"),
+        message_create("MESSAGE_CREATE:1", "1", false, ""),
+    ];
+    feed(&bot, "test", events);
+    let message = tokio::time::timeout(std::time::Duration::from_secs(1), messages.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(message.channel_id, 100);
+    assert_eq!(message.author.id, 1);
+
+    assert_eq!(
+        bot.event_service().required_intents().await,
+        Intents::GUILD_MESSAGES
+    );
+    drop(messages);
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert_eq!(
+        bot.event_service().required_intents().await,
+        Default::default()
+    );
+}
+
+#[tokio::test]
+async fn unread_subscription_does_not_block_others() {
+    use qqbot_sdk::event::{kind, subscribe::SubscribeOptions};
+
+    let bot = test_bot("");
+    let mut messages = bot
+        .event_service()
+        .subscribe_with_options::<kind::MessageCreate>(
+            SubscribeOptions::default().with_queue_size(2),
+        )
+        .await;
+    let mut rx = collect(&bot).await;
+    let events = (0..8)
+        .map(|i| message_create(&format!("MESSAGE_CREATE:{i}"), "1", false, ""))
+        .collect::<Vec<_>>();
+    feed(&bot, "test", events);
+    for i in 0..8 {
+        let envelope = tokio::time::timeout(std::time::Duration::from_secs(1), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(envelope.id, format!("MESSAGE_CREATE:{i}"));
+    }
+    let stats = messages.stats();
+    tokio::time::timeout(std::time::Duration::from_secs(1), async {
+        while stats.pushed() < 8 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(stats.dropped(), 6);
+    // the subscriber still gets the latest events
+    assert!(messages.recv().await.is_some());
+    assert!(messages.recv().await.is_some());
+}
+
+#[tokio::test]
+async fn update_cache_by_guild_and_channel_events() {
+    let bot = test_bot("");
+    let guild = |id: &str, kind: &str, name: &str| {
+        dispatch_envelope(&format!(
+            r#"{{"op":0,"id":"{id}","t":"{kind}","d":{{"description":"","icon":"","id":"2359165788034567890","joined_at":"2021-12-21T14:18:10+08:00","max_members":300,"member_count":4,"name":"{name}","op_user_id":"1","owner_id":"1"}}}}"#
+        ))
+    };
+    let channel = |id: &str, kind: &str, name: &str| {
+        dispatch_envelope(&format!(
+            r#"{{"op":0,"id":"{id}","t":"{kind}","d":{{"guild_id":"2359165788034567890","id":"1402417","name":"{name}","op_user_id":"1","owner_id":"1","sub_type":0,"type":0}}}}"#
+        ))
+    };
+    let run = |events: Vec<EventEnvelope>| {
+        feed(&bot, "test", events);
+        wait_providers(&bot)
+    };
+    let cache = bot.cache();
+
+    run(vec![
+        guild("1", "GUILD_CREATE", "频道"),
+        channel("2", "CHANNEL_CREATE", "子频道"),
+        guild("3", "GUILD_UPDATE", "新频道"),
+        channel("4", "CHANNEL_UPDATE", "新子频道"),
+    ])
+    .await;
+    let cached = cache.get_guild(2359165788034567890).await.unwrap();
+    assert_eq!(cached.name, "新频道");
+    let cached = cache.get_channel(1402417).await.unwrap();
+    assert_eq!(cached.name, "新子频道");
+
+    run(vec![channel("5", "CHANNEL_DELETE", "新子频道")]).await;
+    assert!(cache.get_channel(1402417).await.is_none());
+    assert!(cache.get_guild(2359165788034567890).await.is_some());
+
+    run(vec![
+        channel("6", "CHANNEL_CREATE", "子频道"),
+        guild("7", "GUILD_DELETE", "新频道"),
+    ])
+    .await;
+    assert!(cache.get_guild(2359165788034567890).await.is_none());
+    assert!(
+        cache
+            .get_guild_channels(2359165788034567890)
+            .await
+            .is_empty()
+    );
+}
+
+/// 按照 QQ 的规则，用 bot secret 生成签名
+fn sign_webhook(secret: &str, timestamp: &str, body: &str) -> String {
+    use ed25519_dalek::Signer;
+    let mut seed = [0u8; ed25519_dalek::SECRET_KEY_LENGTH];
+    for (byte, secret) in seed.iter_mut().zip(secret.as_bytes().iter().cycle()) {
+        *byte = *secret;
+    }
+    let key = ed25519_dalek::SigningKey::from_bytes(&seed);
+    hex::encode(key.sign(format!("{timestamp}{body}").as_bytes()).to_bytes())
+}
+
+/// 在本地端口上把 webhook 路由挂载到 `/qqbot/callback`，返回回调地址
+async fn serve_webhook(router: axum::Router) -> String {
+    let app = axum::Router::new().nest_service("/qqbot/callback", router);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/qqbot/callback", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    url
+}
+
+/// 发送用 `secret` 签名的回调
+async fn post_webhook(url: &str, secret: &str, body: &str) -> reqwest::Response {
+    let timestamp = chrono::Utc::now().timestamp().to_string();
+    reqwest::Client::new()
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header("X-Signature-Timestamp", &timestamp)
+        .header(
+            "X-Signature-Ed25519",
+            sign_webhook(secret, &timestamp, body),
+        )
+        .body(body.to_owned())
+        .send()
+        .await
+        .unwrap()
+}
+
+fn friend_add_body(id: &str) -> String {
+    format!(
+        r#"{{"op":0,"id":"{id}","t":"FRIEND_ADD","d":{{"openid":"E4F4AEA33253A2797FB897C50B81D7ED","timestamp":1699250238}}}}"#
+    )
+}
+
+#[tokio::test]
+async fn mounted_webhook_router() {
+    let secret = "123456abcdef";
+    let bot = test_bot(secret);
+    let mut rx = collect(&bot).await;
+    let (router, stats) = bot.webhook_router().unwrap();
+    let url = serve_webhook(router).await;
+    let body = friend_add_body("FRIEND_ADD:1");
+
+    let resp = post_webhook(&url, "another secret", &body).await;
+    assert_eq!(resp.status(), reqwest::StatusCode::UNAUTHORIZED);
+    assert_eq!(stats.pushed(), 0);
+
+    let resp = post_webhook(&url, secret, &body).await;
+    assert_eq!(resp.status(), reqwest::StatusCode::OK);
+    let ack: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(ack["op"], 12);
+    let envelope = tokio::time::timeout(std::time::Duration::from_secs(1), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(envelope.id, "FRIEND_ADD:1");
+    assert_eq!(stats.pushed(), 1);
+}
+
+#[tokio::test]
+async fn full_webhook_queue_rejects_with_503() {
+    use futures_util::StreamExt;
+    use qqbot_sdk::event::implement::webhook::{WebHookRouterConfig, WebHookService};
+
+    let secret = "123456abcdef";
+    // mount without handing the service to a bot, so nothing consumes the queue
+    let (mut service, router) = WebHookService::mount(
+        WebHookRouterConfig {
+            channel_size: 1,
+            ..Default::default()
+        },
+        secret,
+    )
+    .unwrap();
+    let stats = service.stats();
+    let url = serve_webhook(router).await;
+
+    let resp = post_webhook(&url, secret, &friend_add_body("FRIEND_ADD:1")).await;
+    assert_eq!(resp.status(), reqwest::StatusCode::OK);
+    let resp = post_webhook(&url, secret, &friend_add_body("FRIEND_ADD:2")).await;
+    assert_eq!(resp.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(stats.rejected(), 1);
+
+    // the rejected event is not remembered by dedup, the retry is accepted
+    assert_eq!(service.next().await.unwrap().id, "FRIEND_ADD:1");
+    let resp = post_webhook(&url, secret, &friend_add_body("FRIEND_ADD:2")).await;
+    assert_eq!(resp.status(), reqwest::StatusCode::OK);
+    assert_eq!(service.next().await.unwrap().id, "FRIEND_ADD:2");
+}
@@ -20,3 +20,63 @@ fn test_macro() {
         &mut ("&gt;&gt;&gt;&gt;&gt;&gt;<@!123456789> #88888 &lt;&lt;&lt;&lt;&gt;&gt;&gt;&gt;@everyone&lt;&gt;66645".parse::<MessageContent>().unwrap())
     );
 }
+
+#[test]
+fn test_message_sent_timestamp() {
+    use qqbot_sdk::model::MessageSent;
+
+    for json in [
+        r#"{"id":"m","timestamp":"2023-11-06T05:37:18Z"}"#,
+        r#"{"id":"m","timestamp":1699249038}"#,
+        r#"{"id":"m","timestamp":"1699249038"}"#,
+    ] {
+        let sent = serde_json::from_str::<MessageSent>(json).unwrap();
+        assert_eq!(sent.timestamp.unwrap().timestamp(), 1699249038);
+    }
+    let sent = serde_json::from_str::<MessageSent>(r#"{"id":"m"}"#).unwrap();
+    assert!(sent.timestamp.is_none());
+}
+
+#[test]
+fn test_upload_media_body() {
+    use qqbot_sdk::http::api::media::{PostGroupFileRequest, UploadMediaBody};
+    use qqbot_sdk::model::FileType;
+
+    let body = UploadMediaBody::from_url(FileType::Image, "https://example.com/a.png");
+    assert_eq!(
+        serde_json::to_value(&body).unwrap(),
+        serde_json::json!({"file_type":1,"url":"https://example.com/a.png","srv_send_msg":false})
+    );
+
+    let body = UploadMediaBody::from_bytes(FileType::Voice, b"hello").srv_send_msg(true);
+    let request = PostGroupFileRequest {
+        group_openid: "C9F778FE6ADF9D1D1DBE395BF744A33A",
+        body: &body,
+    };
+    assert_eq!(
+        serde_json::to_value(&request).unwrap(),
+        serde_json::json!({"file_type":3,"file_data":"aGVsbG8=","srv_send_msg":true})
+    );
+    let file_type = serde_json::from_str::<FileType>("2").unwrap();
+    assert_eq!(file_type, FileType::Video);
+}
+
+#[test]
+fn test_media_into_message_media() {
+    use qqbot_sdk::model::{Media, MessageMedia};
+
+    let media = serde_json::from_str::<Media>(r#"{"file_uuid":"uuid","file_info":"info","ttl":0}"#)
+        .unwrap();
+    assert!(media.id.is_none());
+    let json = serde_json::to_value(&media).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"file_uuid":"uuid","file_info":"info","ttl":0})
+    );
+    assert_eq!(MessageMedia::from(&media).file_info, "info");
+    let message_media: MessageMedia = media.into();
+    assert_eq!(
+        serde_json::to_value(&message_media).unwrap(),
+        serde_json::json!({"file_info":"info"})
+    );
+}